@@ -57,24 +57,14 @@ fn verify(
             println!("yaml: {:?}", &yaml_map.parsed_sample);
             println!("\n");
 
-            let (only_in_parse, only_in_yaml) =
-                DataRecord::compare_sets(&result, &yaml_map.parsed_sample);
-
-            let mut mismatch_count = 0;
-            for x in &only_in_yaml {
-                mismatch_count += x.len();
-            }
-            for x in &only_in_parse {
-                mismatch_count += x.len();
-            }
-            println!("Only in yaml: {:?}", &only_in_yaml);
-            println!("Only in parse: {:?}", &only_in_parse);
+            let diff = DataRecord::diff(&yaml_map.parsed_sample, &result);
+            println!("{}", diff.to_unified());
             println!("\n");
-            if mismatch_count == 0 {
+            if diff.is_empty() {
                 println!("Results differ, but only by order");
                 Ok(VerifyResult::VerifySuccess)
             } else {
-                println!("Results differ = mismatch count = {}", &mismatch_count);
+                println!("Results differ = {} change(s)", diff.changes.len());
                 Ok(VerifyResult::ResultsDiffer)
             }
         }
@@ -91,10 +81,11 @@ fn collect_file_names(template_dir: &str, extension: &str) -> Result<Vec<String>
         let entry = entry?;
         let path = entry.path();
 
-        if path.is_file() && path.extension().and_then(|ext| ext.to_str()) == Some(extension) {
-            if let Some(base_name) = path.file_stem().and_then(|name| name.to_str()) {
-                base_names.push(base_name.to_string());
-            }
+        if path.is_file()
+            && path.extension().and_then(|ext| ext.to_str()) == Some(extension)
+            && let Some(base_name) = path.file_stem().and_then(|name| name.to_str())
+        {
+            base_names.push(base_name.to_string());
         }
     }
 
@@ -35,11 +35,8 @@ fn main() {
             println!("yaml: {:?}", &yaml_map.parsed_sample);
             println!("\n");
 
-            let (only_in_parse, only_in_yaml) =
-                DataRecord::compare_sets(&result, &yaml_map.parsed_sample);
-
-            println!("Only in yaml: {:?}", &only_in_yaml);
-            println!("Only in parse: {:?}", &only_in_parse);
+            let diff = DataRecord::diff(&yaml_map.parsed_sample, &result);
+            println!("{}", diff.to_unified());
         }
     } else {
         println!("Could not load YAML!");
@@ -0,0 +1,189 @@
+//! Conversion of parse results to Apache Arrow and Parquet.
+//!
+//! [`TextFSM::arrow_schema`] derives a stable schema from the template's Value
+//! declarations: one column per Value, in declaration order, with `List` Values
+//! as list columns. Values typed by the parser's [`Schema`](crate::schema::Schema)
+//! as prefix lengths or VLAN numbers become integer columns; everything else is
+//! text. Empty values are written as nulls.
+//!
+//! ```ignore
+//! let mut fsm = TextFSM::from_file("show_interfaces.textfsm")?;
+//! let records = fsm.parse_file("show_interfaces.txt", None)?;
+//! let batch = record_batch(&records, fsm.arrow_schema(None))?;
+//! write_parquet(&batch, File::create("interfaces.parquet")?)?;
+//! ```
+
+use crate::schema::ValueType;
+use crate::{DataRecord, DataRecordConversion, Result, TextFSM, TextFsmError, Value};
+use arrow_array::builder::{ArrayBuilder, ListBuilder, StringBuilder, UInt8Builder, UInt16Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::sync::Arc;
+
+fn arrow_error<E: std::fmt::Display>(e: E) -> TextFsmError {
+    TextFsmError::InternalError(e.to_string())
+}
+
+fn column_type(value_type: Option<ValueType>) -> DataType {
+    match value_type {
+        Some(ValueType::PrefixLength) => DataType::UInt8,
+        Some(ValueType::VlanId) => DataType::UInt16,
+        _ => DataType::Utf8,
+    }
+}
+
+fn list_type(item: DataType) -> DataType {
+    DataType::List(Arc::new(Field::new("item", item, true)))
+}
+
+impl TextFSM {
+    /// Returns the Arrow schema of this template's records, with the column
+    /// names the pipeline and `conversion` give them.
+    pub fn arrow_schema(&self, conversion: Option<DataRecordConversion>) -> SchemaRef {
+        let pipeline = self.output_pipeline(conversion);
+        let mut fields: Vec<Field> = vec![];
+        for def in self.parser.value_definitions() {
+            let name = pipeline.header(std::slice::from_ref(&def.name)).remove(0);
+            if fields.iter().any(|f| f.name() == &name) {
+                continue;
+            }
+            let value_type = self
+                .schema
+                .as_ref()
+                .and_then(|s| s.groups.get(&def.name))
+                .map(|g| g.value_type);
+            let data_type = if def.is_list {
+                list_type(column_type(value_type))
+            } else {
+                column_type(value_type)
+            };
+            fields.push(Field::new(name, data_type, true));
+        }
+        Arc::new(Schema::new(fields))
+    }
+}
+
+/// Returns a schema for records parsed by an unknown template: text columns
+/// named by the sorted field names, as list columns if any record holds a list.
+pub fn infer_schema(records: &[DataRecord]) -> SchemaRef {
+    let mut columns: BTreeMap<&str, bool> = BTreeMap::new();
+    for rec in records {
+        for (name, value) in &rec.fields {
+            *columns.entry(name).or_default() |= matches!(value, Value::List(_));
+        }
+    }
+    let fields: Vec<Field> = columns
+        .into_iter()
+        .map(|(name, is_list)| {
+            let data_type = if is_list {
+                list_type(DataType::Utf8)
+            } else {
+                DataType::Utf8
+            };
+            Field::new(name, data_type, true)
+        })
+        .collect();
+    Arc::new(Schema::new(fields))
+}
+
+/// Appends one value to a builder of the given item type.
+fn append_item(builder: &mut dyn ArrayBuilder, data_type: &DataType, item: &str) -> Result<()> {
+    let invalid =
+        || TextFsmError::ParseError(format!("'{}' is not a valid {} value", item, data_type));
+    let item = item.trim();
+    match data_type {
+        DataType::UInt8 => {
+            let b = builder.as_any_mut().downcast_mut::<UInt8Builder>().unwrap();
+            match item {
+                "" => b.append_null(),
+                _ => b.append_value(item.parse().map_err(|_| invalid())?),
+            }
+        }
+        DataType::UInt16 => {
+            let b = builder
+                .as_any_mut()
+                .downcast_mut::<UInt16Builder>()
+                .unwrap();
+            match item {
+                "" => b.append_null(),
+                _ => b.append_value(item.parse().map_err(|_| invalid())?),
+            }
+        }
+        _ => {
+            let b = builder
+                .as_any_mut()
+                .downcast_mut::<StringBuilder>()
+                .unwrap();
+            match item {
+                "" => b.append_null(),
+                _ => b.append_value(item),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn column(records: &[DataRecord], field: &Field) -> Result<ArrayRef> {
+    fn item_builder(data_type: &DataType) -> Box<dyn ArrayBuilder> {
+        match data_type {
+            DataType::UInt8 => Box::new(UInt8Builder::new()),
+            DataType::UInt16 => Box::new(UInt16Builder::new()),
+            _ => Box::new(StringBuilder::new()),
+        }
+    }
+
+    match field.data_type() {
+        DataType::List(item) => {
+            let mut builder = ListBuilder::new(item_builder(item.data_type()));
+            for rec in records {
+                match rec.fields.get(field.name()) {
+                    Some(Value::List(items)) => {
+                        for i in items {
+                            append_item(builder.values().as_mut(), item.data_type(), i)?;
+                        }
+                        builder.append(true);
+                    }
+                    Some(Value::Single(s)) if !s.is_empty() => {
+                        append_item(builder.values().as_mut(), item.data_type(), s)?;
+                        builder.append(true);
+                    }
+                    _ => builder.append(false),
+                }
+            }
+            Ok(Arc::new(builder.finish()))
+        }
+        data_type => {
+            let mut builder = item_builder(data_type);
+            for rec in records {
+                let value = match rec.fields.get(field.name()) {
+                    Some(Value::Single(s)) => s.clone(),
+                    Some(Value::List(items)) => items.join(" "),
+                    None => String::new(),
+                };
+                append_item(builder.as_mut(), data_type, &value)?;
+            }
+            Ok(builder.finish())
+        }
+    }
+}
+
+/// Converts records to a `RecordBatch` with the given schema. Fields outside
+/// the schema are dropped; missing and empty values are null.
+pub fn record_batch(records: &[DataRecord], schema: SchemaRef) -> Result<RecordBatch> {
+    let columns = schema
+        .fields()
+        .iter()
+        .map(|f| column(records, f))
+        .collect::<Result<Vec<_>>>()?;
+    RecordBatch::try_new(schema, columns).map_err(arrow_error)
+}
+
+/// Writes a batch as a Parquet file and returns the writer.
+pub fn write_parquet<W: Write + Send>(batch: &RecordBatch, writer: W) -> Result<W> {
+    let mut parquet = ArrowWriter::try_new(writer, batch.schema(), None).map_err(arrow_error)?;
+    parquet.write(batch).map_err(arrow_error)?;
+    parquet.into_inner().map_err(arrow_error)
+}
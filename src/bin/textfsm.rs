@@ -1,22 +1,186 @@
-use clap::{Parser, Subcommand, ValueEnum};
-use std::path::PathBuf;
-use textfsm_rs::{DataRecordConversion, TextFSM};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use std::fs::File;
+use std::io::{BufRead, BufReader, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use textfsm_rs::export::{ExportOptions, RecordMetadata, RecordSink};
+use textfsm_rs::regression::RegressionRunner;
+use textfsm_rs::report::{HtmlReport, ReportSection};
+use textfsm_rs::schema::Schema;
+use textfsm_rs::snapshot::{ChangeDetector, to_json_lines};
+use textfsm_rs::terminal::{self, BorderStyle, Overflow, TableStyle};
+use textfsm_rs::{
+    CliTable, DataRecord, DataRecordConversion, LineRange, TextFSM, TextFsmExport, Value,
+};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
     #[command(subcommand)]
-    command: Commands,
+    command: Option<Commands>,
+
+    /// Template file of the deprecated `textfsm <template> [input]` form
+    #[arg(hide = true)]
+    template: Option<PathBuf>,
+
+    /// Input file of the deprecated `textfsm <template> [input]` form
+    #[arg(hide = true)]
+    input: Option<PathBuf>,
 
     /// Output format
     #[arg(short, long, value_enum, default_value_t = OutputFormat::Yaml, global = true)]
     format: OutputFormat,
+
+    /// Separator joining list items in csv, text, markdown and asciidoc output
+    #[arg(long, default_value = ", ", global = true)]
+    list_separator: String,
+
+    /// Write each list item of text, markdown and asciidoc output on its own line
+    #[arg(long, global = true)]
+    multiline_lists: bool,
+
+    /// Root element of xml output
+    #[arg(long, default_value = "results", global = true)]
+    xml_root: String,
+
+    /// Element of each record in xml output
+    #[arg(long, default_value = "record", global = true)]
+    xml_record: String,
+
+    /// Borders of text output
+    #[arg(long, value_enum, default_value_t = Border::Plain, global = true)]
+    border: Border,
+
+    /// Layout of text output; auto switches to vertical when the table is
+    /// wider than the terminal ($COLUMNS, or 80)
+    #[arg(long, value_enum, default_value_t = Layout::Horizontal, global = true)]
+    layout: Layout,
+
+    /// Wrap text output cells wider than this many columns
+    #[arg(long, global = true)]
+    max_column_width: Option<usize>,
+
+    /// Truncate wide text output cells instead of wrapping them
+    #[arg(long, requires = "max_column_width", global = true)]
+    truncate: bool,
+
+    /// Highlight the header of text output
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto, global = true)]
+    color: ColorChoice,
+}
+
+impl Cli {
+    fn export_options(&self) -> ExportOptions {
+        ExportOptions::new()
+            .with_list_separator(&self.list_separator)
+            .with_multiline_lists(self.multiline_lists)
+            .with_xml_tags(&self.xml_root, &self.xml_record)
+            .with_table_style(self.table_style())
+    }
+
+    fn table_style(&self) -> TableStyle {
+        let border = match self.border {
+            Border::Plain => BorderStyle::Plain,
+            Border::Ascii => BorderStyle::Ascii,
+            Border::Box => BorderStyle::Box,
+        };
+        let layout = match self.layout {
+            Layout::Auto => terminal::Layout::Auto,
+            Layout::Horizontal => terminal::Layout::Horizontal,
+            Layout::Vertical => terminal::Layout::Vertical,
+        };
+        let color = match self.color {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+            }
+        };
+        let columns = std::env::var("COLUMNS")
+            .ok()
+            .and_then(|c| c.parse().ok())
+            .unwrap_or(80);
+        let mut style = TableStyle::new()
+            .with_border(border)
+            .with_layout(layout)
+            .with_max_width(columns)
+            .with_color(color);
+        if let Some(width) = self.max_column_width {
+            let overflow = if self.truncate {
+                Overflow::Truncate
+            } else {
+                Overflow::Wrap
+            };
+            style = style.with_max_column_width(width, overflow);
+        }
+        style
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum Border {
+    /// Columns separated by spaces
+    Plain,
+    /// +---+ borders
+    Ascii,
+    /// Box-drawing characters
+    Box,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum Layout {
+    Auto,
+    /// One row per record
+    Horizontal,
+    /// One block per record
+    Vertical,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum ColorChoice {
+    /// Color when writing to a terminal, unless NO_COLOR is set
+    Auto,
+    Always,
+    Never,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 enum OutputFormat {
     Json,
+    /// One JSON object per line
+    #[value(name = "jsonl", alias = "ndjson")]
+    JsonLines,
     Yaml,
+    Csv,
+    Text,
+    Html,
+    /// GitHub-flavoured Markdown table
+    #[value(alias = "md")]
+    Markdown,
+    /// AsciiDoc table
+    #[value(name = "asciidoc", alias = "adoc")]
+    AsciiDoc,
+    Xml,
+    /// Apache Parquet file, written to stdout
+    #[cfg(feature = "arrow")]
+    Parquet,
+}
+
+impl From<OutputFormat> for textfsm_rs::OutputFormat {
+    fn from(format: OutputFormat) -> Self {
+        match format {
+            OutputFormat::Json => textfsm_rs::OutputFormat::Json,
+            OutputFormat::JsonLines => textfsm_rs::OutputFormat::JsonLines,
+            OutputFormat::Yaml => textfsm_rs::OutputFormat::Yaml,
+            OutputFormat::Csv => textfsm_rs::OutputFormat::Csv,
+            OutputFormat::Text => textfsm_rs::OutputFormat::Text,
+            OutputFormat::Html => textfsm_rs::OutputFormat::Html,
+            OutputFormat::Markdown => textfsm_rs::OutputFormat::Markdown,
+            OutputFormat::AsciiDoc => textfsm_rs::OutputFormat::AsciiDoc,
+            OutputFormat::Xml => textfsm_rs::OutputFormat::Xml,
+            #[cfg(feature = "arrow")]
+            OutputFormat::Parquet => textfsm_rs::OutputFormat::Parquet,
+        }
+    }
 }
 
 #[derive(Subcommand)]
@@ -27,13 +191,21 @@ enum Commands {
         #[arg(short, long)]
         template: PathBuf,
 
-        /// Path to the input data file
+        /// Path to the input data file (reads stdin if omitted)
         #[arg(short, long)]
-        input: PathBuf,
+        input: Option<PathBuf>,
 
         /// Convert keys to lowercase
         #[arg(short, long)]
         lowercase: bool,
+
+        /// Reject records whose common capture groups have values of the wrong type
+        #[arg(long)]
+        check_types: bool,
+
+        /// With jsonl or xml output, add the template, input file and lines of each record
+        #[arg(long)]
+        metadata: bool,
     },
     /// Use CLI Table (ntc-templates index) to parse data
     Auto {
@@ -52,51 +224,591 @@ enum Commands {
         /// Path to the input data file
         #[arg(short, long)]
         input: PathBuf,
+
+        /// With jsonl or xml output, add the platform, command, templates, input file
+        /// and lines of each record
+        #[arg(long)]
+        metadata: bool,
+    },
+    /// Compare two raw outputs of the same command and print the changes as JSON lines
+    Compare {
+        /// Path to the TextFSM template file
+        #[arg(short, long)]
+        template: PathBuf,
+
+        /// The older raw output
+        #[arg(long)]
+        old: PathBuf,
+
+        /// The newer raw output
+        #[arg(long)]
+        new: PathBuf,
+    },
+    /// Run templates against an ntc-templates style tree of samples and expected output
+    Test {
+        /// An ntc-templates checkout, or a `<platform>/<command>/*.raw` tree with --templates
+        dir: PathBuf,
+
+        /// Directory holding the `<platform>_<command>.textfsm` templates
+        #[arg(short, long)]
+        templates: Option<PathBuf>,
+
+        /// Only run cases whose `platform/command/name` contains this string
+        #[arg(long)]
+        filter: Option<String>,
+
+        /// Compare records in order instead of ignoring their order
+        #[arg(long)]
+        ordered: bool,
+
+        /// Write a JUnit XML report to this file
+        #[arg(long)]
+        junit: Option<PathBuf>,
+
+        /// Rewrite the expected YAML of failing cases from the current output
+        #[arg(long)]
+        bless: bool,
+    },
+    /// Print an XSD describing the xml output of a template
+    Xsd {
+        /// Path to the TextFSM template file
+        #[arg(short, long)]
+        template: PathBuf,
+
+        /// Convert keys to lowercase
+        #[arg(short, long)]
+        lowercase: bool,
+    },
+    /// Write a self-contained HTML report with a sortable table per parsed output
+    Report {
+        /// Outputs to parse, as TEMPLATE=INPUT, or COMMAND=INPUT with --index
+        #[arg(required = true)]
+        sections: Vec<String>,
+
+        /// Resolve commands to templates with this index file
+        #[arg(long, requires = "platform")]
+        index: Option<PathBuf>,
+
+        /// Platform name for --index (e.g. cisco_ios)
+        #[arg(short, long)]
+        platform: Option<String>,
+
+        /// Title of the report
+        #[arg(long, default_value = "TextFSM report")]
+        title: String,
+
+        /// Convert keys to lowercase
+        #[arg(short, long)]
+        lowercase: bool,
+    },
+    /// Check template Values against the common capture group names
+    Lint {
+        /// Template files to check
+        #[arg(required = true)]
+        templates: Vec<PathBuf>,
+    },
+    /// Inspect CLI Table index files
+    Index {
+        #[command(subcommand)]
+        action: IndexCommands,
     },
 }
 
-fn main() -> anyhow::Result<()> {
-    let matches = clap::Command::new("textfsm")
-        .version("0.1.0")
-        .author("Author <author@example.com>")
-        .about("TextFSM utility")
-        .arg(
-            clap::Arg::new("template")
-                .help("The template file")
-                .required(true)
-                .index(1),
-        )
-        .arg(
-            clap::Arg::new("input")
-                .help("The input file")
-                .required(false)
-                .index(2),
-        )
-        .get_matches();
-
-    let template = matches.get_one::<String>("template").unwrap();
-    let fsm = TextFSM::from_file(template)?;
-
-    if let Some(input) = matches.get_one::<String>("input") {
-        // We need mutable access for parse_file, but parse_reader takes ownership or mutable ref?
-        // parse_file takes &mut self.
-        // from_file returns a new instance.
-        let mut fsm = fsm;
-        let results = fsm.parse_file(input, Some(DataRecordConversion::LowercaseKeys))?;
-        println!("{}", serde_yaml::to_string(&results)?);
-    } else {
-        let stdin = std::io::stdin();
-        let reader = stdin.lock();
-        // parse_reader consumes self.
-        let iter = fsm.parse_reader(reader);
-        let mut results = Vec::new();
-        for record in iter {
-            results.push(record?);
+#[derive(Subcommand)]
+enum IndexCommands {
+    /// List platforms, commands and templates of an index
+    List {
+        /// Path to an index file; repeat to layer several, highest priority first
+        #[arg(long, required = true)]
+        index: Vec<PathBuf>,
+
+        /// Only list commands of this platform
+        #[arg(short, long)]
+        platform: Option<String>,
+
+        /// Only list platforms supporting this command (e.g. "show version")
+        #[arg(short, long)]
+        command: Option<String>,
+
+        /// Include the Value names of the templates each command maps to
+        #[arg(long)]
+        values: bool,
+    },
+    /// Check index files for missing templates, shadowed commands and other mistakes
+    Check {
+        /// Path to an index file; repeat to layer several, highest priority first
+        #[arg(long, required = true)]
+        index: Vec<PathBuf>,
+    },
+}
+
+/// Records parsed by the templates of an index row, with the input lines of
+/// each record and the template file names.
+struct CliTableResult {
+    /// Value names of the templates, in declaration order.
+    header: Vec<String>,
+    records: Vec<DataRecord>,
+    lines: Vec<LineRange>,
+    templates: Vec<String>,
+    /// Input lines none of the templates matched.
+    unmatched: Vec<(usize, String)>,
+}
+
+fn parse_with_cli_table(
+    index: &PathBuf,
+    platform: &str,
+    command: &str,
+    input: &PathBuf,
+) -> anyhow::Result<CliTableResult> {
+    let cli_table = CliTable::from_file(index)?;
+    let resolved = cli_table.resolve_command(platform, command)?;
+    let parsers = cli_table.get_compiled_templates_for_entry(&resolved.command)?;
+
+    let mut result: Vec<DataRecord> = vec![];
+    let mut header: Vec<String> = vec![];
+    let mut lines: Vec<LineRange> = vec![];
+    let mut unmatched: Option<Vec<(usize, String)>> = None;
+    for parser in parsers {
+        let mut fsm = TextFSM::from_shared(parser).with_unmatched_lines();
+        let new_result = fsm.parse_file(input, Some(DataRecordConversion::LowercaseKeys))?;
+        for name in fsm.header(Some(DataRecordConversion::LowercaseKeys)) {
+            if !header.contains(&name) {
+                header.push(name);
+            }
+        }
+        unmatched = Some(match unmatched {
+            Some(mut prev) => {
+                prev.retain(|line| fsm.unmatched_lines.contains(line));
+                prev
+            }
+            None => std::mem::take(&mut fsm.unmatched_lines),
+        });
+        // merge with the result
+        if result.is_empty() {
+            result = new_result;
+            lines = fsm.parsed_lines().to_vec();
+        } else {
+            for nrow in new_result {
+                if let Some(res) = result.iter_mut().find(|r| r.record_key == nrow.record_key) {
+                    res.overwrite_from(nrow);
+                }
+            }
+        }
+    }
+    Ok(CliTableResult {
+        header,
+        records: result,
+        lines,
+        templates: resolved.command.templates,
+        unmatched: unmatched.unwrap_or_default(),
+    })
+}
+
+/// Parses each `TEMPLATE=INPUT` (or `COMMAND=INPUT` with an index) into a
+/// report section.
+fn build_report(
+    sections: &[String],
+    index: Option<&PathBuf>,
+    platform: Option<&str>,
+    lowercase: bool,
+) -> anyhow::Result<Vec<ReportSection>> {
+    let mut out = vec![];
+    for spec in sections {
+        let Some((name, input)) = spec.split_once('=') else {
+            anyhow::bail!("expected TEMPLATE=INPUT or COMMAND=INPUT, got {:?}", spec);
+        };
+        let input = PathBuf::from(input);
+        let section = match (index, platform) {
+            (Some(index), Some(platform)) => {
+                let result = parse_with_cli_table(index, platform, name, &input)?;
+                ReportSection::new(result.header, result.records)
+                    .with_metadata(RecordMetadata {
+                        template: Some(result.templates.join(":")),
+                        platform: Some(platform.to_string()),
+                        command: Some(name.to_string()),
+                        source: file_name(&input),
+                    })
+                    .with_unmatched(result.unmatched)
+            }
+            _ => {
+                let template = PathBuf::from(name);
+                let mut fsm = TextFSM::from_file(&template)?.with_unmatched_lines();
+                let text = std::fs::read_to_string(&input)?;
+                let conversion = lowercase.then_some(DataRecordConversion::LowercaseKeys);
+                ReportSection::parse(&mut fsm, &text, conversion)?.with_metadata(RecordMetadata {
+                    template: file_name(&template),
+                    source: file_name(&input),
+                    ..Default::default()
+                })
+            }
+        };
+        out.push(section);
+    }
+    Ok(out)
+}
+
+fn list_index(
+    index: &[PathBuf],
+    platform: Option<&str>,
+    command: Option<&str>,
+    values: bool,
+) -> anyhow::Result<Vec<DataRecord>> {
+    let cli_table = CliTable::from_files(index)?;
+    let platforms = match (platform, command) {
+        (Some(p), _) => vec![p.to_string()],
+        (None, Some(cmd)) => cli_table.platforms_for_command(cmd),
+        (None, None) => cli_table.platforms(),
+    };
+
+    let mut records: Vec<DataRecord> = vec![];
+    for platform in platforms {
+        let commands = cli_table.commands_for_platform(&platform);
+        for entry in commands {
+            let lookup = command.unwrap_or(&entry.full_command);
+            if command.is_some() {
+                // Only the row that actually wins the lookup is of interest
+                match cli_table.get_template_for_command(&platform, lookup) {
+                    Some((dir, row))
+                        if dir == entry.template_dir && row.command == entry.command => {}
+                    _ => continue,
+                }
+            }
+            let mut rec = DataRecord::new();
+            rec.insert("PLATFORM".into(), entry.platform.clone());
+            rec.insert("COMMAND".into(), entry.command.clone());
+            rec.insert("FULL_COMMAND".into(), entry.full_command.clone());
+            rec.fields
+                .insert("TEMPLATES".into(), Value::List(entry.templates.clone()));
+            if values {
+                let names = cli_table
+                    .template_values_for_command(&platform, lookup)?
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|v| v.name().to_string())
+                    .collect();
+                rec.fields.insert("VALUES".into(), Value::List(names));
+            }
+            records.push(rec);
+        }
+    }
+    Ok(records)
+}
+
+fn check_index(index: &[PathBuf]) -> anyhow::Result<(Vec<DataRecord>, usize)> {
+    let cli_table = CliTable::from_files(index)?;
+    let issues = cli_table.validate();
+    let errors = issues.iter().filter(|i| i.kind.is_error()).count();
+
+    let records = issues
+        .into_iter()
+        .map(|issue| {
+            let mut rec = DataRecord::new();
+            rec.insert("INDEX".into(), issue.index);
+            rec.insert(
+                "ROW".into(),
+                issue.row.map(|r| r.to_string()).unwrap_or_default(),
+            );
+            rec.insert("KIND".into(), format!("{:?}", issue.kind));
+            rec.insert("MESSAGE".into(), issue.message);
+            rec
+        })
+        .collect();
+    Ok((records, errors))
+}
+
+/// Writes records to stdout as they are parsed, so unbounded input can be processed.
+fn stream_records(
+    fsm: TextFSM,
+    reader: Box<dyn BufRead>,
+    format: textfsm_rs::OutputFormat,
+    options: ExportOptions,
+    metadata: Option<RecordMetadata>,
+) -> anyhow::Result<()> {
+    let mut header = fsm.header(None);
+    header.sort();
+    let mut sink = RecordSink::new(std::io::stdout().lock(), format)
+        .with_header(header)
+        .with_options(options);
+    #[cfg(feature = "arrow")]
+    {
+        sink = sink.with_arrow_schema(fsm.arrow_schema(None));
+    }
+    if let Some(metadata) = metadata {
+        sink = sink.with_metadata(metadata);
+    }
+    let mut records = fsm.parse_reader(reader);
+    while let Some(record) = records.next() {
+        sink.write_record_at(&record?, records.last_lines())?;
+    }
+    finish_stream(sink)
+}
+
+/// Finishes streamed output, ending it with a newline where the format does not.
+fn finish_stream<W: Write>(sink: RecordSink<W>) -> anyhow::Result<()> {
+    let format = sink.format();
+    let mut writer = sink.finish()?;
+    if matches!(
+        format,
+        textfsm_rs::OutputFormat::Json
+            | textfsm_rs::OutputFormat::Html
+            | textfsm_rs::OutputFormat::Xml
+    ) {
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/// Record metadata is only written in JSON lines and XML output.
+fn check_metadata_format(metadata: bool, format: OutputFormat) -> anyhow::Result<()> {
+    if metadata && !matches!(format, OutputFormat::JsonLines | OutputFormat::Xml) {
+        anyhow::bail!("--metadata requires --format jsonl or xml");
+    }
+    Ok(())
+}
+
+fn file_name(path: &Path) -> Option<String> {
+    path.file_name().map(|n| n.to_string_lossy().into_owned())
+}
+
+/// Prints exported records, ending text output with a newline unless it already has one.
+fn print_records(
+    records: &Vec<DataRecord>,
+    format: OutputFormat,
+    options: &ExportOptions,
+) -> anyhow::Result<()> {
+    let format = textfsm_rs::OutputFormat::from(format);
+    let mut output = vec![];
+    records.export_to_with(format, options, &mut output)?;
+    if !format.is_binary() && !output.ends_with(b"\n") {
+        output.push(b'\n');
+    }
+    std::io::stdout().lock().write_all(&output)?;
+    Ok(())
+}
+
+fn lint_templates(templates: &[PathBuf]) -> anyhow::Result<Vec<DataRecord>> {
+    let schema = Schema::common();
+    let mut records = vec![];
+    for template in templates {
+        let fsm = TextFSM::from_file(template)?;
+        for issue in schema.lint(&fsm.parser) {
+            let mut rec = DataRecord::new();
+            rec.insert("TEMPLATE".into(), template.display().to_string());
+            rec.insert("VALUE".into(), issue.value);
+            rec.insert("KIND".into(), format!("{:?}", issue.kind));
+            rec.insert("MESSAGE".into(), issue.message);
+            records.push(rec);
         }
-        if !results.is_empty() {
-            println!("{}", serde_yaml::to_string(&results)?);
+    }
+    Ok(records)
+}
+
+fn compare_outputs(template: &Path, old: &Path, new: &Path) -> anyhow::Result<String> {
+    let mut fsm = TextFSM::from_file(template)?;
+    let detector = ChangeDetector::from_parser(&fsm.parser);
+    let old_records = fsm.parse_file(old, None)?;
+    let mut fsm = TextFSM::from_shared(fsm.parser);
+    let new_records = fsm.parse_file(new, None)?;
+    Ok(to_json_lines(&detector.detect(&old_records, &new_records))?)
+}
+
+fn run_tests(
+    dir: &Path,
+    templates: Option<&Path>,
+    filter: Option<&str>,
+    ordered: bool,
+    junit: Option<&Path>,
+    bless: bool,
+) -> anyhow::Result<()> {
+    let mut runner = match templates {
+        Some(templates) => RegressionRunner::new(templates, dir),
+        None => RegressionRunner::from_checkout(dir),
+    };
+    runner.filter = filter.map(str::to_string);
+    runner.ordered = ordered;
+
+    let report = runner.run()?;
+    let mut blessed = 0;
+    for result in &report.results {
+        if bless && result.bless()? {
+            blessed += 1;
+            println!("BLESS {}", result.case.id());
+        } else {
+            println!("{}", result);
         }
     }
+    if let Some(junit) = junit {
+        std::fs::write(junit, report.to_junit())?;
+    }
+    println!("{}", report);
+
+    let unresolved = report.results.len() - report.passed() - blessed;
+    if unresolved > 0 {
+        anyhow::bail!("{} case(s) did not pass", unresolved);
+    }
+    Ok(())
+}
+
+/// Parses like `textfsm <template> [input]` did before there were
+/// subcommands: keys are lowercased when reading a file, but not stdin.
+fn legacy_parse(cli: &Cli, template: &Path, input: Option<&Path>) -> anyhow::Result<()> {
+    eprintln!(
+        "warning: `textfsm <template> [input]` is deprecated, use `textfsm parse --template <template> [--input <input>]`"
+    );
+    let mut fsm = TextFSM::from_file(template)?;
+    let results = match input {
+        Some(input) => fsm.parse_file(input, Some(DataRecordConversion::LowercaseKeys))?,
+        None => {
+            let results = fsm
+                .parse_reader(std::io::stdin().lock())
+                .collect::<textfsm_rs::Result<Vec<_>>>()?;
+            if results.is_empty() {
+                return Ok(());
+            }
+            results
+        }
+    };
+    print_records(&results, cli.format, &cli.export_options())
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
+    let Some(command) = &cli.command else {
+        let Some(template) = &cli.template else {
+            Cli::command().print_help()?;
+            std::process::exit(2);
+        };
+        return legacy_parse(&cli, template, cli.input.as_deref());
+    };
+    let results = match command {
+        Commands::Parse {
+            template,
+            input,
+            lowercase,
+            check_types,
+            metadata,
+        } => {
+            check_metadata_format(*metadata, cli.format)?;
+            let conversion = lowercase.then_some(DataRecordConversion::LowercaseKeys);
+            let mut fsm = TextFSM::from_file(template)?.with_pipeline(conversion.into());
+            if *check_types {
+                fsm = fsm.with_schema(Schema::common());
+            }
+            let reader: Box<dyn BufRead> = match input {
+                Some(input) => Box::new(BufReader::new(File::open(input)?)),
+                None => Box::new(std::io::stdin().lock()),
+            };
+            let metadata = metadata.then(|| RecordMetadata {
+                template: file_name(template),
+                source: input.as_deref().and_then(file_name),
+                ..Default::default()
+            });
+            stream_records(
+                fsm,
+                reader,
+                cli.format.into(),
+                cli.export_options(),
+                metadata,
+            )?;
+            return Ok(());
+        }
+        Commands::Auto {
+            index,
+            platform,
+            command,
+            input,
+            metadata,
+        } => {
+            check_metadata_format(*metadata, cli.format)?;
+            let result = parse_with_cli_table(index, platform, command, input)?;
+            if *metadata {
+                let mut sink = RecordSink::new(std::io::stdout().lock(), cli.format.into())
+                    .with_options(cli.export_options())
+                    .with_metadata(RecordMetadata {
+                        template: Some(result.templates.join(":")),
+                        platform: Some(platform.clone()),
+                        command: Some(command.clone()),
+                        source: file_name(input),
+                    });
+                for (i, record) in result.records.iter().enumerate() {
+                    sink.write_record_at(record, result.lines.get(i).copied())?;
+                }
+                return finish_stream(sink);
+            }
+            result.records
+        }
+        Commands::Compare { template, old, new } => {
+            print!("{}", compare_outputs(template, old, new)?);
+            return Ok(());
+        }
+        Commands::Test {
+            dir,
+            templates,
+            filter,
+            ordered,
+            junit,
+            bless,
+        } => {
+            return run_tests(
+                dir,
+                templates.as_deref(),
+                filter.as_deref(),
+                *ordered,
+                junit.as_deref(),
+                *bless,
+            );
+        }
+        Commands::Xsd {
+            template,
+            lowercase,
+        } => {
+            let fsm = TextFSM::from_file(template)?;
+            let conversion = lowercase.then_some(DataRecordConversion::LowercaseKeys);
+            print!("{}", fsm.xml_schema(conversion, &cli.export_options()));
+            return Ok(());
+        }
+        Commands::Report {
+            sections,
+            index,
+            platform,
+            title,
+            lowercase,
+        } => {
+            let mut report = HtmlReport::new(title);
+            for section in build_report(sections, index.as_ref(), platform.as_deref(), *lowercase)?
+            {
+                report.add_section(section);
+            }
+            report.write_to(&mut std::io::stdout().lock())?;
+            return Ok(());
+        }
+        Commands::Lint { templates } => {
+            let records = lint_templates(templates)?;
+            print_records(&records, cli.format, &cli.export_options())?;
+            if !records.is_empty() {
+                anyhow::bail!("{} issue(s) found", records.len());
+            }
+            return Ok(());
+        }
+        Commands::Index { action } => match action {
+            IndexCommands::List {
+                index,
+                platform,
+                command,
+                values,
+            } => list_index(index, platform.as_deref(), command.as_deref(), *values)?,
+            IndexCommands::Check { index } => {
+                let (records, errors) = check_index(index)?;
+                print_records(&records, cli.format, &cli.export_options())?;
+                if errors > 0 {
+                    anyhow::bail!("{} error(s) found in index", errors);
+                }
+                return Ok(());
+            }
+        },
+    };
 
+    print_records(&results, cli.format, &cli.export_options())?;
     Ok(())
 }
@@ -1,16 +1,24 @@
-use crate::{Result, TextFsmError};
+use crate::embed::EmbeddedTemplates;
+use crate::source::{FileSource, TemplateSource};
+use crate::{Result, TextFSMParser, TextFsmError, ValueDefinition};
 use fancy_regex::Regex;
 use log::{debug, trace};
 use std::collections::HashMap;
-use std::path::Path;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
 
 /// Represents a CLI table index file parsed into memory.
 #[derive(Debug, Clone)]
 pub struct ParsedCliTable {
     /// The filename of the index.
     pub fname: String,
+    /// The column names from the header line, in file order.
+    pub columns: Vec<String>,
     /// The rows of the table.
     pub rows: Vec<CliTableRow>,
+    /// Where the index and its templates are read from.
+    pub source: Arc<dyn TemplateSource>,
 }
 
 /// A high-level interface for command-to-template mapping using index files.
@@ -20,6 +28,68 @@ pub struct CliTable {
     pub tables: Vec<ParsedCliTable>,
     /// Map of platform names to their associated regex rules for command matching.
     pub platform_regex_rules: HashMap<String, Vec<CliTableRegexRule>>,
+    /// Compiled templates, shared between clones of this table.
+    pub template_cache: Arc<TemplateCache>,
+}
+
+/// A thread-safe cache of compiled templates keyed by their file path.
+#[derive(Debug, Default)]
+pub struct TemplateCache {
+    templates: RwLock<HashMap<PathBuf, Arc<TextFSMParser>>>,
+}
+
+impl TemplateCache {
+    /// Returns the compiled template at `path`, compiling and caching it on first use.
+    pub fn get_or_compile<P: AsRef<Path>>(&self, path: P) -> Result<Arc<TextFSMParser>> {
+        let path = path.as_ref();
+        self.get_or_compile_with(path, || TextFSMParser::from_file(path))
+    }
+
+    /// Returns the template cached under `path`, calling `compile` to build it on first use.
+    pub fn get_or_compile_with<F>(&self, path: &Path, compile: F) -> Result<Arc<TextFSMParser>>
+    where
+        F: FnOnce() -> Result<TextFSMParser>,
+    {
+        if let Some(parser) = self.read_lock()?.get(path) {
+            return Ok(parser.clone());
+        }
+        // Compile without holding the lock; if another thread won the race, keep theirs.
+        debug!("Compiling template {}", path.display());
+        let parser = Arc::new(compile()?);
+        let mut templates = self
+            .templates
+            .write()
+            .map_err(|e| TextFsmError::InternalError(e.to_string()))?;
+        Ok(templates
+            .entry(path.to_path_buf())
+            .or_insert(parser)
+            .clone())
+    }
+
+    /// Returns the number of compiled templates in the cache.
+    pub fn len(&self) -> usize {
+        self.read_lock().map(|t| t.len()).unwrap_or(0)
+    }
+
+    /// Returns `true` if no template has been compiled yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops all compiled templates, forcing them to be re-read on next use.
+    pub fn clear(&self) {
+        if let Ok(mut templates) = self.templates.write() {
+            templates.clear();
+        }
+    }
+
+    fn read_lock(
+        &self,
+    ) -> Result<std::sync::RwLockReadGuard<'_, HashMap<PathBuf, Arc<TextFSMParser>>>> {
+        self.templates
+            .read()
+            .map_err(|e| TextFsmError::InternalError(e.to_string()))
+    }
 }
 
 /// A rule for matching a command to a specific row in an index table.
@@ -46,11 +116,93 @@ pub struct CliTableRow {
     pub command: String,
 }
 
+/// A command supported by a platform, as listed by [`CliTable::commands_for_platform`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CliTableCommand {
+    /// The platform the command belongs to.
+    pub platform: String,
+    /// The command as written in the index, with `[[abbrev]]` markers.
+    pub command: String,
+    /// The command in its full, unabbreviated form.
+    pub full_command: String,
+    /// Template filenames the command maps to.
+    pub templates: Vec<String>,
+    /// Directory the templates are resolved against.
+    pub template_dir: String,
+    /// Index into `CliTable::tables` of the index the command comes from.
+    pub table_index: usize,
+    /// Index into the `rows` of that table.
+    pub row_index: usize,
+}
+
+/// A row matching a command, as returned by [`CliTable::matching_commands`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandMatch {
+    /// The matching command entry.
+    pub command: CliTableCommand,
+    /// Number of characters of the index command that are not abbreviable.
+    ///
+    /// A row that requires more of the command to be typed out is more specific.
+    pub specificity: usize,
+}
+
+/// The kind of problem found by [`CliTable::validate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexIssueKind {
+    /// A template listed in a row does not exist.
+    MissingTemplate,
+    /// A template listed in a row exists but does not compile.
+    BrokenTemplate,
+    /// The same command appears more than once for a platform.
+    DuplicateCommand,
+    /// An earlier row of the same index matches every form of this row's command,
+    /// so this row can never be selected.
+    ShadowedCommand,
+    /// A `[[` without a closing `]]` (or the reverse); it is matched literally.
+    UnbalancedBrackets,
+    /// A column the index parser does not understand; its values are ignored.
+    UnknownColumn,
+}
+
+impl IndexIssueKind {
+    /// Returns `true` for problems that break lookups, `false` for warnings.
+    pub fn is_error(&self) -> bool {
+        !matches!(self, IndexIssueKind::UnknownColumn)
+    }
+}
+
+/// A problem found in a CLI table index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexIssue {
+    /// What kind of problem it is.
+    pub kind: IndexIssueKind,
+    /// The `fname` of the index the problem is in.
+    pub index: String,
+    /// The 1-based data row the problem is on, if it concerns a single row.
+    pub row: Option<usize>,
+    /// A human-readable description.
+    pub message: String,
+}
+
+impl fmt::Display for IndexIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.row {
+            Some(row) => write!(
+                f,
+                "{} row {}: {:?}: {}",
+                self.index, row, self.kind, self.message
+            ),
+            None => write!(f, "{}: {:?}: {}", self.index, self.kind, self.message),
+        }
+    }
+}
+
 impl ParsedCliTable {
-    fn parse(fname: &Path) -> Result<Vec<CliTableRow>> {
-        use std::io::BufReader;
-        let file = std::fs::File::open(fname)?;
-        let reader = BufReader::new(file);
+    /// Column names understood by the index parser.
+    pub const KNOWN_COLUMNS: [&'static str; 5] =
+        ["Template", "Hostname", "Platform", "Vendor", "Command"];
+
+    fn parse<R: std::io::Read>(reader: R) -> Result<(Vec<String>, Vec<CliTableRow>)> {
         let mut rows: Vec<CliTableRow> = vec![];
         let mut rdr = csv::ReaderBuilder::new()
             .comment(Some(b'#'))
@@ -60,7 +212,8 @@ impl ParsedCliTable {
             .from_reader(reader);
         trace!("Reader");
 
-        let headers: Vec<&str> = rdr.headers()?.into_iter().collect();
+        let columns: Vec<String> = rdr.headers()?.iter().map(|h| h.to_string()).collect();
+        let headers: Vec<&str> = columns.iter().map(|h| h.as_str()).collect();
         trace!("Headers: {:?}", &headers);
 
         if !headers.contains(&"Template") {
@@ -102,17 +255,32 @@ impl ParsedCliTable {
             };
             rows.push(row);
         }
-        Ok(rows)
+        Ok((columns, rows))
     }
 
     /// Loads and parses a CLI table index from a file.
     pub fn from_file<P: AsRef<Path>>(fname: P) -> Result<Self> {
-        let path = fname.as_ref();
-        debug!("Loading cli table from {}", path.display());
-        let rows = Self::parse(path)?;
+        Self::from_source(Arc::new(FileSource::from_index(fname)))
+    }
+
+    /// Loads and parses the index of a `TemplateSource`.
+    ///
+    /// The table's `fname` is the source location joined with its index name, so for
+    /// sources that are not on disk (`embedded:<name>/index`, `memory:<name>/index`)
+    /// template paths derived from it never collide with files.
+    pub fn from_source(source: Arc<dyn TemplateSource>) -> Result<Self> {
+        let fname = Path::new(&source.location())
+            .join(source.index_name())
+            .to_string_lossy()
+            .into_owned();
+        debug!("Loading cli table from {}", &fname);
+        let content = source.read_index()?;
+        let (columns, rows) = Self::parse(content.as_bytes())?;
         Ok(ParsedCliTable {
-            fname: path.to_string_lossy().into_owned(),
+            fname,
+            columns,
             rows,
+            source,
         })
     }
 }
@@ -170,60 +338,642 @@ impl CliTable {
         result
     }
 
+    /// Returns the full form of an index command by dropping the `[[ ]]` markers.
+    /// E.g., "sh[[ow]] ve[[rsion]]" -> "show version"
+    pub fn full_command(input: &str) -> String {
+        let mut result = String::with_capacity(input.len());
+        let mut current_pos = 0;
+
+        while let Some(start) = input[current_pos..].find("[[") {
+            result.push_str(&input[current_pos..current_pos + start]);
+            let content_start = current_pos + start + 2;
+            if let Some(end) = input[content_start..].find("]]") {
+                result.push_str(&input[content_start..content_start + end]);
+                current_pos = content_start + end + 2;
+            } else {
+                // Same as expand_brackets: an unmatched [[ stays literal
+                result.push_str("[[");
+                current_pos = content_start;
+            }
+        }
+
+        result.push_str(&input[current_pos..]);
+        result
+    }
+
+    /// Returns the names of all platforms in the loaded indexes, sorted.
+    ///
+    /// Rows of an index without a platform column are listed as `no-platform`.
+    pub fn platforms(&self) -> Vec<String> {
+        let mut platforms: Vec<String> = self.platform_regex_rules.keys().cloned().collect();
+        platforms.sort();
+        platforms
+    }
+
+    /// Lists the commands of a platform in lookup priority order.
+    pub fn commands_for_platform(&self, platform: &str) -> Vec<CliTableCommand> {
+        let Some(rules) = self.platform_regex_rules.get(platform) else {
+            return vec![];
+        };
+        rules
+            .iter()
+            .map(|rule| self.command_entry(platform, rule))
+            .collect()
+    }
+
+    fn command_entry(&self, platform: &str, rule: &CliTableRegexRule) -> CliTableCommand {
+        let table = &self.tables[rule.table_index];
+        let row = &table.rows[rule.row_index];
+        CliTableCommand {
+            platform: platform.to_string(),
+            command: row.command.clone(),
+            full_command: Self::full_command(&row.command),
+            templates: row.templates.clone(),
+            template_dir: Self::get_directory(&table.fname).unwrap_or_default(),
+            table_index: rule.table_index,
+            row_index: rule.row_index,
+        }
+    }
+
+    /// Normalizes a typed command: lowercase, trimmed, with single spaces between words.
+    pub fn normalize_command(cmd: &str) -> String {
+        cmd.split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+            .to_lowercase()
+    }
+
+    /// Returns the number of characters of an index command outside `[[ ]]` markers.
+    fn specificity(command: &str) -> usize {
+        Self::full_command(command)
+            .chars()
+            .count()
+            .saturating_sub(Self::abbreviable_len(command))
+    }
+
+    /// Returns the number of characters inside `[[ ]]` markers.
+    fn abbreviable_len(command: &str) -> usize {
+        let mut len = 0;
+        let mut rest = command;
+        while let Some(start) = rest.find("[[") {
+            let content = &rest[start + 2..];
+            let Some(end) = content.find("]]") else {
+                break;
+            };
+            len += content[..end].chars().count();
+            rest = &content[end + 2..];
+        }
+        len
+    }
+
+    /// Returns all rows of a platform matching a command, most specific first.
+    ///
+    /// The command is normalized with [`CliTable::normalize_command`] before matching,
+    /// and index commands match regardless of case.
+    /// Rows with the same specificity keep their lookup priority order.
+    pub fn matching_commands(&self, platform: &str, cmd: &str) -> Vec<CommandMatch> {
+        let Some(rules) = self.platform_regex_rules.get(platform) else {
+            return vec![];
+        };
+        let normalized = Self::normalize_command(cmd);
+        let mut matches: Vec<CommandMatch> = rules
+            .iter()
+            .filter(|rule| {
+                rule.command_regex
+                    .is_match(&normalized)
+                    .expect("Fancy regex ok?")
+            })
+            .map(|rule| {
+                let command = self.command_entry(platform, rule);
+                let specificity = Self::specificity(&command.command);
+                CommandMatch {
+                    command,
+                    specificity,
+                }
+            })
+            .collect();
+        // stable sort, so equally specific rows stay in priority order
+        matches.sort_by_key(|m| std::cmp::Reverse(m.specificity));
+        matches
+    }
+
+    /// Resolves a typed command to exactly one row of a platform.
+    ///
+    /// Unlike [`CliTable::get_template_for_command`], which returns the first regex hit,
+    /// this fails with `TextFsmError::CommandError` when the most specific matches are
+    /// different commands, listing the candidates. A row overridden by a higher-priority
+    /// index layer for the same command is not considered ambiguous. When nothing
+    /// matches, the error suggests the closest known command, if any is close enough.
+    pub fn resolve_command(&self, platform: &str, cmd: &str) -> Result<CommandMatch> {
+        let matches = self.matching_commands(platform, cmd);
+        let Some(best) = matches.first() else {
+            let hint = match self.suggest_command(platform, cmd) {
+                Some(suggestion) => format!(", did you mean '{}'?", suggestion),
+                None => String::new(),
+            };
+            return Err(TextFsmError::CommandError(format!(
+                "No template for command '{}' on platform '{}'{}",
+                cmd, platform, hint
+            )));
+        };
+        let mut candidates: Vec<&str> = vec![];
+        for m in matches
+            .iter()
+            .take_while(|m| m.specificity == best.specificity)
+        {
+            if !candidates.contains(&m.command.full_command.as_str()) {
+                candidates.push(&m.command.full_command);
+            }
+        }
+        if candidates.len() > 1 {
+            return Err(TextFsmError::CommandError(format!(
+                "Ambiguous command '{}' on platform '{}', could be: '{}'",
+                cmd,
+                platform,
+                candidates.join("', '")
+            )));
+        }
+        Ok(best.clone())
+    }
+
+    /// Suggests the known command of a platform closest to a command that did not match.
+    ///
+    /// Words are compared pairwise, and a typed word that abbreviates the command's
+    /// word costs nothing, so `show ip intreface brief` suggests
+    /// `show ip interface brief`. Both commands are normalized, so case is ignored as
+    /// in matching. Returns `None` if no command is reasonably close.
+    pub fn suggest_command(&self, platform: &str, cmd: &str) -> Option<String> {
+        let normalized = Self::normalize_command(cmd);
+        let typed: Vec<&str> = normalized.split(' ').collect();
+        let max_distance = (normalized.chars().count() / 3).max(2);
+
+        let mut best: Option<(usize, String)> = None;
+        for entry in self.commands_for_platform(platform) {
+            let full_command = Self::normalize_command(&entry.full_command);
+            let words: Vec<&str> = full_command.split(' ').collect();
+            let mut distance = 0;
+            for i in 0..typed.len().max(words.len()) {
+                distance += match (typed.get(i), words.get(i)) {
+                    (Some(t), Some(w)) => Self::word_distance(t, w),
+                    (Some(x), None) | (None, Some(x)) => x.chars().count(),
+                    (None, None) => 0,
+                };
+            }
+            if distance <= max_distance && best.as_ref().is_none_or(|(d, _)| distance < *d) {
+                best = Some((distance, entry.full_command));
+            }
+        }
+        best.map(|(_, suggestion)| suggestion)
+    }
+
+    /// Edit distance between a typed word and a command word it may abbreviate.
+    fn word_distance(typed: &str, word: &str) -> usize {
+        if word.starts_with(typed) {
+            return 0;
+        }
+        let prefix: String = word.chars().take(typed.chars().count()).collect();
+        Self::levenshtein(typed, word).min(Self::levenshtein(typed, &prefix))
+    }
+
+    fn levenshtein(a: &str, b: &str) -> usize {
+        let b: Vec<char> = b.chars().collect();
+        let mut prev: Vec<usize> = (0..=b.len()).collect();
+        for (i, ca) in a.chars().enumerate() {
+            let mut curr = vec![i + 1; b.len() + 1];
+            for (j, cb) in b.iter().enumerate() {
+                let cost = if ca == *cb { 0 } else { 1 };
+                curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+            }
+            prev = curr;
+        }
+        prev[b.len()]
+    }
+
+    /// Returns the platforms that have a template for the given command, sorted.
+    pub fn platforms_for_command(&self, cmd: &str) -> Vec<String> {
+        self.platforms()
+            .into_iter()
+            .filter(|platform| self.find_rule(platform, cmd).is_some())
+            .collect()
+    }
+
+    /// Returns the Value definitions produced by the templates a command maps to.
+    ///
+    /// Values are listed in declaration order; when a row lists several templates, a
+    /// Value declared by more than one of them is reported once, as first declared.
+    /// Returns `Ok(None)` if no row matches the command.
+    pub fn template_values_for_command(
+        &self,
+        platform: &str,
+        cmd: &str,
+    ) -> Result<Option<Vec<ValueDefinition>>> {
+        let Some(parsers) = self.get_compiled_templates_for_command(platform, cmd)? else {
+            return Ok(None);
+        };
+        let mut values: Vec<ValueDefinition> = vec![];
+        for parser in &parsers {
+            for value in parser.value_definitions() {
+                if !values.iter().any(|v| v.name == value.name) {
+                    values.push(value.clone());
+                }
+            }
+        }
+        Ok(Some(values))
+    }
+
     fn get_directory(filename: &str) -> Option<String> {
         let path = Path::new(filename);
         path.parent().map(|p| p.to_string_lossy().into_owned())
     }
 
     /// Finds the appropriate template and row information for a given platform and command.
+    ///
+    /// The returned directory is the one containing the index that supplied the row, so
+    /// templates from different layers resolve against their own index location.
     pub fn get_template_for_command(
         &self,
         platform: &str,
         cmd: &str,
     ) -> Option<(String, CliTableRow)> {
+        let rule = self.find_rule(platform, cmd)?;
+        let row = self.tables[rule.table_index].rows[rule.row_index].clone();
+        let fdir = Self::get_directory(&self.tables[rule.table_index].fname)?;
+        Some((fdir, row))
+    }
+
+    fn find_rule(&self, platform: &str, cmd: &str) -> Option<&CliTableRegexRule> {
         let plat_regex_list = self.platform_regex_rules.get(platform)?;
-        for rule in plat_regex_list {
-            if rule.command_regex.is_match(cmd).expect("Fancy regex ok?") {
-                let row = self.tables[rule.table_index].rows[rule.row_index].clone();
-                let fname = &self.tables[rule.table_index].fname;
-                if let Some(fdir) = Self::get_directory(fname) {
-                    return Some((fdir, row));
+        plat_regex_list.iter().find(|rule| {
+            rule.command_regex.is_match(cmd).expect("Fancy regex ok?")
+                && Self::get_directory(&self.tables[rule.table_index].fname).is_some()
+        })
+    }
+
+    /// Compiles (or fetches from the cache) one template listed in a row of `table`.
+    fn compile_template(
+        &self,
+        table: &ParsedCliTable,
+        dir: &str,
+        name: &str,
+    ) -> Result<Arc<TextFSMParser>> {
+        let path = Path::new(dir).join(name);
+        self.template_cache
+            .get_or_compile_with(&path, || TextFSMParser::from_source(&*table.source, name))
+    }
+
+    /// Returns the paths of the templates listed in `row`, resolved against `dir`.
+    pub fn template_paths(dir: &str, row: &CliTableRow) -> Vec<PathBuf> {
+        row.templates
+            .iter()
+            .map(|name| Path::new(dir).join(name))
+            .collect()
+    }
+
+    /// Finds the templates for a command and returns them compiled.
+    ///
+    /// Templates are compiled lazily on first use and kept in `template_cache`, so
+    /// repeated lookups of the same command do not touch the filesystem again.
+    /// Returns `Ok(None)` if no row matches the command.
+    pub fn get_compiled_templates_for_command(
+        &self,
+        platform: &str,
+        cmd: &str,
+    ) -> Result<Option<Vec<Arc<TextFSMParser>>>> {
+        let Some(rule) = self.find_rule(platform, cmd) else {
+            return Ok(None);
+        };
+        self.compile_row_templates(rule.table_index, rule.row_index)
+            .map(Some)
+    }
+
+    /// Returns the compiled templates of a command entry, e.g. one from `resolve_command`.
+    pub fn get_compiled_templates_for_entry(
+        &self,
+        entry: &CliTableCommand,
+    ) -> Result<Vec<Arc<TextFSMParser>>> {
+        self.compile_row_templates(entry.table_index, entry.row_index)
+    }
+
+    fn compile_row_templates(
+        &self,
+        table_index: usize,
+        row_index: usize,
+    ) -> Result<Vec<Arc<TextFSMParser>>> {
+        let table = &self.tables[table_index];
+        let dir = Self::get_directory(&table.fname).unwrap_or_default();
+        table.rows[row_index]
+            .templates
+            .iter()
+            .map(|name| self.compile_template(table, &dir, name))
+            .collect()
+    }
+
+    /// Checks all loaded indexes for problems that only surface at lookup time.
+    ///
+    /// Reports rows whose templates are missing or do not compile, duplicate and
+    /// shadowed commands within a platform of the same index, unbalanced `[[ ]]`
+    /// markers, and unknown columns. Rows overridden by a higher-priority index
+    /// layer are intentional and not reported. Templates are compiled into the cache.
+    pub fn validate(&self) -> Vec<IndexIssue> {
+        let mut issues: Vec<IndexIssue> = vec![];
+        for (table_index, table) in self.tables.iter().enumerate() {
+            let issue = |kind, row: Option<usize>, message: String| IndexIssue {
+                kind,
+                index: table.fname.clone(),
+                row: row.map(|r| r + 1),
+                message,
+            };
+
+            for column in &table.columns {
+                if !ParsedCliTable::KNOWN_COLUMNS.contains(&column.as_str()) {
+                    issues.push(issue(
+                        IndexIssueKind::UnknownColumn,
+                        None,
+                        format!("unknown column '{}'", column),
+                    ));
+                }
+            }
+
+            let dir = Self::get_directory(&table.fname).unwrap_or_default();
+            let mut checked_templates: Vec<&str> = vec![];
+            for (row_index, row) in table.rows.iter().enumerate() {
+                if Self::has_unbalanced_brackets(&row.command) {
+                    issues.push(issue(
+                        IndexIssueKind::UnbalancedBrackets,
+                        Some(row_index),
+                        format!("unbalanced '[[' ']]' in command '{}'", row.command),
+                    ));
+                }
+                for name in &row.templates {
+                    if checked_templates.contains(&name.as_str()) {
+                        continue;
+                    }
+                    checked_templates.push(name);
+                    if let Err(e) = self.compile_template(table, &dir, name) {
+                        let kind = match &e {
+                            TextFsmError::IoError(io)
+                                if io.kind() == std::io::ErrorKind::NotFound =>
+                            {
+                                IndexIssueKind::MissingTemplate
+                            }
+                            _ => IndexIssueKind::BrokenTemplate,
+                        };
+                        issues.push(issue(
+                            kind,
+                            Some(row_index),
+                            format!("template '{}': {}", name, e),
+                        ));
+                    }
+                }
+            }
+
+            for rules in self.platform_regex_rules.values() {
+                let rules: Vec<&CliTableRegexRule> = rules
+                    .iter()
+                    .filter(|r| r.table_index == table_index)
+                    .collect();
+                for (i, later) in rules.iter().enumerate() {
+                    let later_row = &table.rows[later.row_index];
+                    let forms = Self::command_forms(&later_row.command);
+                    let covering = rules[..i].iter().find(|earlier| {
+                        table.rows[earlier.row_index].command == later_row.command
+                            || forms
+                                .iter()
+                                .all(|form| earlier.command_regex.is_match(form).unwrap_or(false))
+                    });
+                    let Some(earlier) = covering else {
+                        continue;
+                    };
+                    let earlier_row = &table.rows[earlier.row_index];
+                    let platform = later_row.platform.as_deref().unwrap_or("no-platform");
+                    let (kind, what) = if earlier_row.command == later_row.command {
+                        (IndexIssueKind::DuplicateCommand, "duplicates")
+                    } else {
+                        (IndexIssueKind::ShadowedCommand, "is shadowed by")
+                    };
+                    issues.push(issue(
+                        kind,
+                        Some(later.row_index),
+                        format!(
+                            "command '{}' on platform '{}' {} row {} ('{}')",
+                            later_row.command,
+                            platform,
+                            what,
+                            earlier.row_index + 1,
+                            earlier_row.command
+                        ),
+                    ));
                 }
             }
         }
-        None
+        issues.sort_by(|a, b| (&a.index, a.row).cmp(&(&b.index, b.row)));
+        issues
     }
 
-    /// Loads a CLI table from an index file and compiles all command regexes.
-    pub fn from_file<P: AsRef<Path>>(fname: P) -> Result<Self> {
-        let parsed_cli_table = ParsedCliTable::from_file(fname)?;
-        let tables = vec![parsed_cli_table];
-        let mut platform_regex_rules: HashMap<String, Vec<CliTableRegexRule>> = Default::default();
+    fn has_unbalanced_brackets(command: &str) -> bool {
+        let mut rest = command;
+        loop {
+            let open = rest.find("[[");
+            let close = rest.find("]]");
+            match (open, close) {
+                (None, None) => return false,
+                (Some(o), Some(c)) if o < c => rest = &rest[c + 2..],
+                _ => return true,
+            }
+        }
+    }
 
-        for (table_index, table) in tables.iter().enumerate() {
-            for (row_index, row) in table.rows.iter().enumerate() {
-                let expanded_command = Self::expand_brackets(&row.command);
-                let anchored_command = format!("^{}$", expanded_command);
-                let command_regex = Regex::new(&anchored_command)
-                    .map_err(|e| TextFsmError::ParseError(e.to_string()))?;
-
-                let rule = CliTableRegexRule {
-                    table_index,
-                    row_index,
-                    command_regex,
+    /// Returns the ways a command can be typed: every combination of abbreviations
+    /// of its `[[ ]]` groups. Only the shortest, the longest and each group varied on
+    /// its own are returned when there are too many combinations.
+    fn command_forms(command: &str) -> Vec<String> {
+        const MAX_FORMS: usize = 4096;
+        // Split into literal text and abbreviable groups.
+        let mut parts: Vec<(String, Vec<char>)> = vec![];
+        let mut rest = command;
+        while let Some(start) = rest.find("[[") {
+            let content = &rest[start + 2..];
+            let Some(end) = content.find("]]") else {
+                break;
+            };
+            parts.push((rest[..start].to_string(), content[..end].chars().collect()));
+            rest = &content[end + 2..];
+        }
+        let tail = rest.to_string();
+
+        let build = |lens: &[usize]| {
+            let mut form = String::new();
+            for ((text, group), len) in parts.iter().zip(lens) {
+                form.push_str(text);
+                form.extend(&group[..*len]);
+            }
+            form.push_str(&tail);
+            form
+        };
+
+        let full: Vec<usize> = parts.iter().map(|(_, g)| g.len()).collect();
+        let combinations = full
+            .iter()
+            .try_fold(1usize, |acc, len| acc.checked_mul(len + 1));
+        if combinations.is_some_and(|c| c <= MAX_FORMS) {
+            let mut forms: Vec<String> = vec![];
+            let mut lens = vec![0; parts.len()];
+            loop {
+                forms.push(build(&lens));
+                // odometer increment
+                let Some(pos) = (0..lens.len()).find(|&i| lens[i] < full[i]) else {
+                    break;
                 };
-                let no_platform = "no-platform".to_string();
-                let platform_name: &str = row.platform.as_ref().unwrap_or(&no_platform);
-                platform_regex_rules
-                    .entry(platform_name.into())
-                    .or_default()
-                    .push(rule);
+                lens[pos] += 1;
+                for l in lens.iter_mut().take(pos) {
+                    *l = 0;
+                }
+            }
+            forms
+        } else {
+            let mut forms = vec![build(&vec![0; parts.len()]), build(&full)];
+            for i in 0..parts.len() {
+                for len in 0..=full[i] {
+                    let mut lens = full.clone();
+                    lens[i] = len;
+                    forms.push(build(&lens));
+                }
             }
+            forms
         }
-        Ok(CliTable {
-            platform_regex_rules,
-            tables,
-        })
+    }
+
+    /// Eagerly compiles every template referenced by the loaded indexes.
+    ///
+    /// Compilation does not stop at the first failure: the returned list holds every
+    /// template that could not be read or compiled, and is empty when all are valid.
+    pub fn precompile_templates(&self) -> Vec<(PathBuf, TextFsmError)> {
+        let mut failures: Vec<(PathBuf, TextFsmError)> = vec![];
+        for table in &self.tables {
+            let dir = Self::get_directory(&table.fname).unwrap_or_default();
+            for row in &table.rows {
+                for name in &row.templates {
+                    let path = Path::new(&dir).join(name);
+                    if failures.iter().any(|(p, _)| *p == path) {
+                        continue;
+                    }
+                    if let Err(e) = self.compile_template(table, &dir, name) {
+                        failures.push((path, e));
+                    }
+                }
+            }
+        }
+        failures
+    }
+
+    /// Creates an empty CLI table with no index layers.
+    pub fn new() -> Self {
+        CliTable {
+            tables: vec![],
+            platform_regex_rules: HashMap::new(),
+            template_cache: Default::default(),
+        }
+    }
+
+    /// Loads a CLI table from an index file and compiles all command regexes.
+    pub fn from_file<P: AsRef<Path>>(fname: P) -> Result<Self> {
+        let mut cli_table = Self::new();
+        cli_table.add_index(fname)?;
+        Ok(cli_table)
+    }
+
+    /// Loads a CLI table from the index of an embedded template library.
+    pub fn from_embedded(templates: &EmbeddedTemplates) -> Result<Self> {
+        Self::from_source(*templates)
+    }
+
+    /// Loads a CLI table from the index of any `TemplateSource`.
+    pub fn from_source<S: TemplateSource + 'static>(source: S) -> Result<Self> {
+        let mut cli_table = Self::new();
+        cli_table.add_source(source)?;
+        Ok(cli_table)
+    }
+
+    /// Loads a layered CLI table from several index files.
+    ///
+    /// Indexes are consulted in the order given: when more than one layer has a row
+    /// matching a command, the row from the earliest index wins. Put site-local
+    /// overrides first and the upstream (e.g. ntc-templates) index last.
+    pub fn from_files<I, P>(fnames: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut cli_table = Self::new();
+        for fname in fnames {
+            cli_table.add_index(fname)?;
+        }
+        Ok(cli_table)
+    }
+
+    /// Adds another index file as the lowest-priority layer of this table.
+    ///
+    /// Rows from the new index only match commands that no previously added index
+    /// already handles for the same platform. Template names in each row are
+    /// resolved relative to the directory of the index they came from.
+    pub fn add_index<P: AsRef<Path>>(&mut self, fname: P) -> Result<()> {
+        self.add_table(ParsedCliTable::from_file(fname)?)
+    }
+
+    /// Adds the index of an embedded template library as the lowest-priority layer.
+    pub fn add_embedded_index(&mut self, templates: &EmbeddedTemplates) -> Result<()> {
+        self.add_source(*templates)
+    }
+
+    /// Adds the index of a `TemplateSource` as the lowest-priority layer.
+    pub fn add_source<S: TemplateSource + 'static>(&mut self, source: S) -> Result<()> {
+        self.add_table(ParsedCliTable::from_source(Arc::new(source))?)
+    }
+
+    fn add_table(&mut self, table: ParsedCliTable) -> Result<()> {
+        let table_index = self.tables.len();
+        let mut new_rules: Vec<(String, CliTableRegexRule)> = vec![];
+
+        for (row_index, row) in table.rows.iter().enumerate() {
+            let expanded_command = Self::expand_brackets(&row.command);
+            // Commands are matched regardless of case, so index commands written with
+            // capitals still match the lowercased form from `normalize_command`
+            let anchored_command = format!("(?i)^{}$", expanded_command);
+            let command_regex = Regex::new(&anchored_command).map_err(|e| {
+                TextFsmError::ParseError(format!(
+                    "{} row {}: invalid command '{}': {}",
+                    table.fname,
+                    row_index + 1,
+                    row.command,
+                    e
+                ))
+            })?;
+
+            let rule = CliTableRegexRule {
+                table_index,
+                row_index,
+                command_regex,
+            };
+            let platform_name = row.platform.as_deref().unwrap_or("no-platform");
+            new_rules.push((platform_name.to_string(), rule));
+        }
+
+        // Only register the layer once every row compiled, so a bad index leaves the
+        // table untouched.
+        for (platform_name, rule) in new_rules {
+            self.platform_regex_rules
+                .entry(platform_name)
+                .or_default()
+                .push(rule);
+        }
+        self.tables.push(table);
+        Ok(())
+    }
+}
+
+impl Default for CliTable {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -240,6 +990,63 @@ mod tests {
         assert_eq!(CliTable::expand_string("show"), "(s(h(o(w)?)?)?)?");
     }
 
+    #[test]
+    fn test_normalize_command() {
+        assert_eq!(
+            CliTable::normalize_command("  Show  IP\tint brief "),
+            "show ip int brief"
+        );
+    }
+
+    #[test]
+    fn test_specificity() {
+        assert_eq!(CliTable::specificity("show"), 4);
+        assert_eq!(CliTable::specificity("sh[[ow]] i[[nterfaces]]"), 4);
+        assert_eq!(CliTable::specificity("sh[[ow]] in[[ventory]]"), 5);
+    }
+
+    #[test]
+    fn test_word_distance() {
+        assert_eq!(CliTable::word_distance("int", "interface"), 0);
+        assert_eq!(CliTable::word_distance("intreface", "interface"), 2);
+        assert_eq!(CliTable::word_distance("brie", "brief"), 0);
+        assert_eq!(CliTable::word_distance("bref", "brief"), 1);
+        assert_eq!(CliTable::levenshtein("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_full_command() {
+        assert_eq!(CliTable::full_command("show"), "show");
+        assert_eq!(CliTable::full_command("sh[[ow]]"), "show");
+        assert_eq!(
+            CliTable::full_command("sh[[ow]] ip bgp su[[mmary]]"),
+            "show ip bgp summary"
+        );
+        assert_eq!(CliTable::full_command("sh[[ow"), "sh[[ow");
+    }
+
+    #[test]
+    fn test_has_unbalanced_brackets() {
+        assert!(!CliTable::has_unbalanced_brackets("show"));
+        assert!(!CliTable::has_unbalanced_brackets("sh[[ow]] ip [[bgp]]"));
+        assert!(CliTable::has_unbalanced_brackets("sh[[ow"));
+        assert!(CliTable::has_unbalanced_brackets("show]] ip"));
+        assert!(CliTable::has_unbalanced_brackets("sh[[ow]] i[[p"));
+    }
+
+    #[test]
+    fn test_command_forms() {
+        assert_eq!(CliTable::command_forms("show"), vec!["show"]);
+        assert_eq!(
+            CliTable::command_forms("sh[[ow]]"),
+            vec!["sh", "sho", "show"]
+        );
+        assert_eq!(
+            CliTable::command_forms("s[[h]] i[[p]]"),
+            vec!["s i", "sh i", "s ip", "sh ip"]
+        );
+    }
+
     #[test]
     fn test_expand_brackets() {
         assert_eq!(CliTable::expand_brackets("show"), "show");
@@ -0,0 +1,468 @@
+//! Structured differences between two sets of records.
+//!
+//! [`diff_records`] aligns the records of an old and a new result set and
+//! reports which records were added, removed, changed (with per-field old and
+//! new values) or moved. Records are aligned by the values of key fields when
+//! known, otherwise by equality first and then by the largest number of shared
+//! field values. A [`RecordSetDiff`] renders as a unified text diff or, with
+//! the `json` feature, as JSON.
+
+use crate::{DataRecord, RecordKey, Value};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// How records are aligned and which differences are reported.
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    /// Fields identifying a record, usually the template's `Key` Values.
+    ///
+    /// When empty, records are aligned by `record_key` if every record has one,
+    /// otherwise by best match.
+    pub key_fields: Vec<String>,
+    /// Also report records that are present on both sides but changed position
+    /// relative to the others.
+    pub report_moves: bool,
+}
+
+impl DiffOptions {
+    /// Aligns records by the given key fields.
+    pub fn with_keys<S: AsRef<str>>(key_fields: &[S]) -> Self {
+        DiffOptions {
+            key_fields: key_fields.iter().map(|k| k.as_ref().to_string()).collect(),
+            report_moves: false,
+        }
+    }
+}
+
+/// The old and new value of one field; `None` means the field is absent.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    /// Field name.
+    pub field: String,
+    /// Value in the old record.
+    pub old: Option<Value>,
+    /// Value in the new record.
+    pub new: Option<Value>,
+}
+
+/// One difference between two record sets.
+///
+/// Indexes are 0-based positions in the old and new record lists.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum RecordChange {
+    /// A record only present in the new set.
+    Added {
+        new_index: usize,
+        record: DataRecord,
+    },
+    /// A record only present in the old set.
+    Removed {
+        old_index: usize,
+        record: DataRecord,
+    },
+    /// A record present in both sets with different field values.
+    Changed {
+        old_index: usize,
+        new_index: usize,
+        /// The key the records were aligned on, if any.
+        key: Option<RecordKey>,
+        fields: Vec<FieldChange>,
+    },
+    /// A record present in both sets whose position relative to the others changed.
+    Moved {
+        old_index: usize,
+        new_index: usize,
+        key: Option<RecordKey>,
+    },
+}
+
+/// The differences between two record sets, in the order: changed and moved
+/// records by old position, removed records, added records.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RecordSetDiff {
+    /// All differences found.
+    pub changes: Vec<RecordChange>,
+}
+
+impl RecordSetDiff {
+    /// Returns `true` if both record sets are equivalent.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Records only present in the new set.
+    pub fn added(&self) -> impl Iterator<Item = &DataRecord> {
+        self.changes.iter().filter_map(|c| match c {
+            RecordChange::Added { record, .. } => Some(record),
+            _ => None,
+        })
+    }
+
+    /// Records only present in the old set.
+    pub fn removed(&self) -> impl Iterator<Item = &DataRecord> {
+        self.changes.iter().filter_map(|c| match c {
+            RecordChange::Removed { record, .. } => Some(record),
+            _ => None,
+        })
+    }
+
+    /// Records present in both sets with different field values.
+    pub fn changed(&self) -> impl Iterator<Item = &RecordChange> {
+        self.changes
+            .iter()
+            .filter(|c| matches!(c, RecordChange::Changed { .. }))
+    }
+
+    /// Renders the differences as a unified, line-oriented text diff.
+    ///
+    /// Removed records start with `-`, added ones with `+`, changed ones with `~`
+    /// followed by one indented line per field, and moved ones with `>`.
+    pub fn to_unified(&self) -> String {
+        self.to_string()
+    }
+
+    /// Renders the differences as pretty-printed JSON.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> crate::Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| crate::TextFsmError::InternalError(e.to_string()))
+    }
+}
+
+fn fmt_value(value: &Option<Value>) -> String {
+    match value {
+        None => "<absent>".to_string(),
+        Some(Value::Single(s)) => format!("{:?}", s),
+        Some(Value::List(l)) => format!("{:?}", l),
+    }
+}
+
+fn fmt_record(record: &DataRecord) -> String {
+    let fields: BTreeMap<&String, &Value> = record.fields.iter().collect();
+    fields
+        .into_iter()
+        .map(|(k, v)| format!("{}: {}", k, fmt_value(&Some(v.clone()))))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn fmt_key(key: &Option<RecordKey>) -> String {
+    key.as_ref().map(|k| format!(" {}", k)).unwrap_or_default()
+}
+
+impl fmt::Display for RecordChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecordChange::Removed { old_index, record } => {
+                write!(f, "- [{}] {{{}}}", old_index + 1, fmt_record(record))
+            }
+            RecordChange::Added { new_index, record } => {
+                write!(f, "+ [{}] {{{}}}", new_index + 1, fmt_record(record))
+            }
+            RecordChange::Changed {
+                old_index,
+                new_index,
+                key,
+                fields,
+            } => {
+                write!(
+                    f,
+                    "~ [{} -> {}]{}",
+                    old_index + 1,
+                    new_index + 1,
+                    fmt_key(key)
+                )?;
+                for change in fields {
+                    write!(
+                        f,
+                        "\n    {}: {} -> {}",
+                        change.field,
+                        fmt_value(&change.old),
+                        fmt_value(&change.new)
+                    )?;
+                }
+                Ok(())
+            }
+            RecordChange::Moved {
+                old_index,
+                new_index,
+                key,
+            } => write!(
+                f,
+                "> [{} -> {}]{}",
+                old_index + 1,
+                new_index + 1,
+                fmt_key(key)
+            ),
+        }
+    }
+}
+
+impl fmt::Display for RecordSetDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, change) in self.changes.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares the fields of two records, ignoring `record_key`. Fields are sorted by name.
+pub fn diff_fields(old: &DataRecord, new: &DataRecord) -> Vec<FieldChange> {
+    let mut names: Vec<&String> = old.fields.keys().chain(new.fields.keys()).collect();
+    names.sort();
+    names.dedup();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let o = old.get(name);
+            let n = new.get(name);
+            (o != n).then(|| FieldChange {
+                field: name.clone(),
+                old: o.cloned(),
+                new: n.cloned(),
+            })
+        })
+        .collect()
+}
+
+/// Pairs old and new records; returns `(old_index, new_index, key)` triples.
+fn align(
+    old: &[DataRecord],
+    new: &[DataRecord],
+    options: &DiffOptions,
+) -> Vec<(usize, usize, Option<RecordKey>)> {
+    let keyed_by_record_key = options.key_fields.is_empty()
+        && !old.is_empty()
+        && !new.is_empty()
+        && old.iter().chain(new).all(|r| r.record_key.is_some());
+
+    let key_of = |r: &DataRecord| -> Option<RecordKey> {
+        if !options.key_fields.is_empty() {
+            RecordKey::from_record(r, &options.key_fields)
+        } else if keyed_by_record_key {
+            r.record_key.clone()
+        } else {
+            None
+        }
+    };
+
+    let mut pairs: Vec<(usize, usize, Option<RecordKey>)> = vec![];
+    let mut used = vec![false; new.len()];
+
+    if !options.key_fields.is_empty() || keyed_by_record_key {
+        // Records with equal keys are paired in order of appearance.
+        let mut by_key: HashMap<RecordKey, Vec<usize>> = HashMap::new();
+        for (j, rec) in new.iter().enumerate() {
+            if let Some(key) = key_of(rec) {
+                by_key.entry(key).or_default().push(j);
+            }
+        }
+        for (i, rec) in old.iter().enumerate() {
+            let Some(key) = key_of(rec) else {
+                continue;
+            };
+            if let Some(candidates) = by_key.get_mut(&key)
+                && !candidates.is_empty()
+            {
+                let j = candidates.remove(0);
+                used[j] = true;
+                pairs.push((i, j, Some(key)));
+            }
+        }
+        return pairs;
+    }
+
+    let mut leftover: Vec<usize> = vec![];
+    for (i, rec) in old.iter().enumerate() {
+        match (0..new.len()).find(|&j| !used[j] && new[j].fields == rec.fields) {
+            Some(j) => {
+                used[j] = true;
+                pairs.push((i, j, None));
+            }
+            None => leftover.push(i),
+        }
+    }
+    for i in leftover {
+        let shared = |j: usize| {
+            old[i]
+                .fields
+                .iter()
+                .filter(|(k, v)| new[j].get(k) == Some(v))
+                .count()
+        };
+        let best = (0..new.len())
+            .filter(|&j| !used[j])
+            .map(|j| (shared(j), j))
+            .filter(|(score, _)| *score > 0)
+            // highest score, then lowest index
+            .max_by_key(|&(score, j)| (score, std::cmp::Reverse(j)));
+        if let Some((_, j)) = best {
+            used[j] = true;
+            pairs.push((i, j, None));
+        }
+    }
+    pairs
+}
+
+/// Returns the positions (into `seq`) of a longest strictly increasing subsequence.
+fn longest_increasing(seq: &[usize]) -> Vec<usize> {
+    // tails[k]: position of the smallest tail of an increasing run of length k + 1
+    let mut tails: Vec<usize> = vec![];
+    let mut prev: Vec<Option<usize>> = vec![None; seq.len()];
+    for (pos, &value) in seq.iter().enumerate() {
+        let k = tails.partition_point(|&t| seq[t] < value);
+        prev[pos] = k.checked_sub(1).map(|k| tails[k]);
+        if k == tails.len() {
+            tails.push(pos);
+        } else {
+            tails[k] = pos;
+        }
+    }
+    let mut result: Vec<usize> = vec![];
+    let mut cur = tails.last().copied();
+    while let Some(pos) = cur {
+        result.push(pos);
+        cur = prev[pos];
+    }
+    result.reverse();
+    result
+}
+
+/// Computes the differences between an old and a new set of records.
+pub fn diff_records(
+    old: &[DataRecord],
+    new: &[DataRecord],
+    options: &DiffOptions,
+) -> RecordSetDiff {
+    let mut pairs = align(old, new, options);
+    pairs.sort_by_key(|(i, _, _)| *i);
+
+    let mut in_order = vec![true; pairs.len()];
+    if options.report_moves {
+        in_order = vec![false; pairs.len()];
+        let new_positions: Vec<usize> = pairs.iter().map(|(_, j, _)| *j).collect();
+        for pos in longest_increasing(&new_positions) {
+            in_order[pos] = true;
+        }
+    }
+
+    let mut changes: Vec<RecordChange> = vec![];
+    for ((i, j, key), in_order) in pairs.iter().zip(in_order) {
+        let fields = diff_fields(&old[*i], &new[*j]);
+        if !fields.is_empty() {
+            changes.push(RecordChange::Changed {
+                old_index: *i,
+                new_index: *j,
+                key: key.clone(),
+                fields,
+            });
+        } else if !in_order {
+            changes.push(RecordChange::Moved {
+                old_index: *i,
+                new_index: *j,
+                key: key.clone(),
+            });
+        }
+    }
+
+    let mut old_used = vec![false; old.len()];
+    let mut new_used = vec![false; new.len()];
+    for (i, j, _) in &pairs {
+        old_used[*i] = true;
+        new_used[*j] = true;
+    }
+    for (i, _) in old_used.iter().enumerate().filter(|(_, u)| !**u) {
+        changes.push(RecordChange::Removed {
+            old_index: i,
+            record: old[i].clone(),
+        });
+    }
+    for (j, _) in new_used.iter().enumerate().filter(|(_, u)| !**u) {
+        changes.push(RecordChange::Added {
+            new_index: j,
+            record: new[j].clone(),
+        });
+    }
+    RecordSetDiff { changes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rec(fields: &[(&str, &str)]) -> DataRecord {
+        let mut r = DataRecord::new();
+        for (k, v) in fields {
+            r.insert(k.to_string(), v.to_string());
+        }
+        r
+    }
+
+    #[test]
+    fn test_longest_increasing() {
+        assert_eq!(longest_increasing(&[]), Vec::<usize>::new());
+        assert_eq!(longest_increasing(&[0, 1, 2]), vec![0, 1, 2]);
+        assert_eq!(longest_increasing(&[2, 0, 1]), vec![1, 2]);
+        assert_eq!(longest_increasing(&[1, 0, 2, 4, 3]).len(), 3);
+    }
+
+    #[test]
+    fn test_diff_best_match() {
+        let a = rec(&[("name", "a"), ("mtu", "1500")]);
+        let b = rec(&[("name", "b"), ("mtu", "1500")]);
+        let b2 = rec(&[("name", "b"), ("mtu", "9000")]);
+        let c = rec(&[("name", "c"), ("mtu", "1")]);
+
+        let options = DiffOptions::default();
+        assert!(
+            diff_records(&[a.clone(), b.clone()], &[b.clone(), a.clone()], &options).is_empty()
+        );
+
+        let diff = diff_records(&[a.clone(), b], &[c.clone(), b2, a], &options);
+        assert_eq!(
+            diff.changes,
+            vec![
+                RecordChange::Changed {
+                    old_index: 1,
+                    new_index: 1,
+                    key: None,
+                    fields: vec![FieldChange {
+                        field: "mtu".to_string(),
+                        old: Some(Value::Single("1500".to_string())),
+                        new: Some(Value::Single("9000".to_string())),
+                    }],
+                },
+                RecordChange::Added {
+                    new_index: 0,
+                    record: c,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_moves() {
+        let a = rec(&[("name", "a")]);
+        let b = rec(&[("name", "b")]);
+        let c = rec(&[("name", "c")]);
+        let options = DiffOptions {
+            report_moves: true,
+            ..Default::default()
+        };
+        let diff = diff_records(&[a.clone(), b.clone(), c.clone()], &[b, c, a], &options);
+        assert_eq!(
+            diff.changes,
+            vec![RecordChange::Moved {
+                old_index: 0,
+                new_index: 2,
+                key: None
+            }]
+        );
+    }
+}
@@ -0,0 +1,205 @@
+//! Bundling template libraries into a binary at compile time.
+//!
+//! A build script calls [`generate`] on a template directory (for example an
+//! ntc-templates checkout). It writes a small Rust file into `OUT_DIR` that
+//! `include_str!`s every file of the directory. The crate then pulls that file
+//! in with [`include_templates!`](crate::include_templates) and gets an
+//! [`EmbeddedTemplates`] value. It can load a `CliTable` and compile templates
+//! from it without touching the filesystem at runtime.
+//!
+//! ```ignore
+//! // build.rs
+//! fn main() {
+//!     textfsm_rs::embed::generate("ntc", "ntc_templates/templates", true).unwrap();
+//! }
+//!
+//! // src/main.rs
+//! static NTC: textfsm_rs::embed::EmbeddedTemplates = textfsm_rs::include_templates!("ntc");
+//! let cli_table = textfsm_rs::CliTable::from_embedded(&NTC)?;
+//! ```
+
+use crate::source::{TemplateSource, collect_files};
+use crate::{Result, TextFSMParser, TextFsmError};
+use log::debug;
+use std::path::{Path, PathBuf};
+
+/// Name of the index file inside a template library.
+pub const INDEX_NAME: &str = "index";
+
+/// Extension of the templates validated in a library without an index.
+pub const TEMPLATE_EXTENSION: &str = "textfsm";
+
+/// A template library compiled into the binary.
+///
+/// Files are addressed by their path relative to the embedded directory, using
+/// `/` as the separator regardless of the build host.
+#[derive(Debug, Clone, Copy)]
+pub struct EmbeddedTemplates {
+    /// Name given to the library when it was generated.
+    pub name: &'static str,
+    /// `(relative path, content)` pairs, sorted by path.
+    pub files: &'static [(&'static str, &'static str)],
+}
+
+impl EmbeddedTemplates {
+    /// Creates a library from a static list of files.
+    pub const fn new(name: &'static str, files: &'static [(&'static str, &'static str)]) -> Self {
+        EmbeddedTemplates { name, files }
+    }
+
+    /// Returns the content of a file, if it is part of the library.
+    pub fn get(&self, fname: &str) -> Option<&'static str> {
+        self.files
+            .iter()
+            .find(|(name, _)| *name == fname)
+            .map(|(_, content)| *content)
+    }
+
+    /// Returns the content of a file, or an error naming the missing file.
+    pub fn read(&self, fname: &str) -> Result<&'static str> {
+        self.get(fname).ok_or_else(|| {
+            TextFsmError::IoError(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("{} not found in embedded templates '{}'", fname, self.name),
+            ))
+        })
+    }
+
+    /// Returns an iterator over the relative paths of all files.
+    pub fn file_names(&self) -> impl Iterator<Item = &'static str> {
+        self.files.iter().map(|(name, _)| *name)
+    }
+
+    /// Compiles the named template.
+    pub fn parser(&self, fname: &str) -> Result<TextFSMParser> {
+        TextFSMParser::from_source(self, fname)
+    }
+}
+
+impl TemplateSource for EmbeddedTemplates {
+    fn location(&self) -> String {
+        format!("embedded:{}", self.name)
+    }
+
+    fn read_template(&self, name: &str) -> Result<String> {
+        self.read(name).map(str::to_string)
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        Ok(self.file_names().map(str::to_string).collect())
+    }
+}
+
+/// Includes a template library generated by [`generate`] in the build script.
+///
+/// Expands to an [`EmbeddedTemplates`] value, so it can initialise a `static`.
+#[macro_export]
+macro_rules! include_templates {
+    ($name:literal) => {
+        include!(concat!(
+            env!("OUT_DIR"),
+            "/textfsm_templates_",
+            $name,
+            ".rs"
+        ))
+    };
+}
+
+/// Generates the embedding module for `dir` into `OUT_DIR`. Call from a build script.
+///
+/// When `validate` is set every template is compiled first, and the build fails
+/// with a list of all broken templates. The templates are those the index
+/// references or, without an index, the files ending in `.textfsm`; other
+/// files such as a README are embedded but not compiled.
+pub fn generate<P: AsRef<Path>>(name: &str, dir: P, validate: bool) -> Result<PathBuf> {
+    let out_dir = std::env::var("OUT_DIR")
+        .map_err(|_| TextFsmError::InternalError("OUT_DIR is not set".to_string()))?;
+    println!("cargo:rerun-if-changed={}", dir.as_ref().display());
+    generate_to(name, dir, out_dir, validate)
+}
+
+/// Same as [`generate`], but writes into an explicit output directory.
+pub fn generate_to<P: AsRef<Path>, Q: AsRef<Path>>(
+    name: &str,
+    dir: P,
+    out_dir: Q,
+    validate: bool,
+) -> Result<PathBuf> {
+    let dir = dir.as_ref();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(TextFsmError::ParseError(format!(
+            "Invalid embedded template library name {:?}",
+            name
+        )));
+    }
+
+    let mut files: Vec<(String, PathBuf)> = vec![];
+    collect_files(dir, "", &mut files)?;
+    files.sort();
+
+    if validate {
+        validate_templates(dir, &files)?;
+    }
+
+    let mut module = String::from("// @generated by textfsm_rs::embed, do not edit.\n");
+    module.push_str(&format!(
+        "::textfsm_rs::embed::EmbeddedTemplates::new({:?}, &[\n",
+        name
+    ));
+    for (rel_name, path) in &files {
+        let abs_path = std::fs::canonicalize(path)?;
+        module.push_str(&format!(
+            "    ({:?}, include_str!({:?})),\n",
+            rel_name,
+            abs_path.to_string_lossy()
+        ));
+    }
+    module.push_str("])\n");
+
+    let out_file = out_dir
+        .as_ref()
+        .join(format!("textfsm_templates_{}.rs", name));
+    debug!(
+        "Embedding {} files from {} into {}",
+        files.len(),
+        dir.display(),
+        out_file.display()
+    );
+    std::fs::write(&out_file, module)?;
+    Ok(out_file)
+}
+
+fn validate_templates(dir: &Path, files: &[(String, PathBuf)]) -> Result<()> {
+    let mut failures: Vec<String> = vec![];
+
+    #[cfg(feature = "clitable")]
+    if files.iter().any(|(name, _)| name == INDEX_NAME) {
+        let cli_table = crate::CliTable::from_file(dir.join(INDEX_NAME))?;
+        for (path, e) in cli_table.precompile_templates() {
+            failures.push(format!("{}: {}", path.display(), e));
+        }
+        return report_failures(dir, failures);
+    }
+
+    for (_, path) in files {
+        if path.extension().and_then(|ext| ext.to_str()) != Some(TEMPLATE_EXTENSION) {
+            continue;
+        }
+        if let Err(e) = TextFSMParser::from_file(path) {
+            failures.push(format!("{}: {}", path.display(), e));
+        }
+    }
+    report_failures(dir, failures)
+}
+
+fn report_failures(dir: &Path, failures: Vec<String>) -> Result<()> {
+    if failures.is_empty() {
+        return Ok(());
+    }
+    Err(TextFsmError::ParseError(format!(
+        "{} broken template(s) in {}:\n{}",
+        failures.len(),
+        dir.display(),
+        failures.join("\n")
+    )))
+}
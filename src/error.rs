@@ -11,9 +11,16 @@ pub enum TextFsmError {
     #[cfg(any(feature = "clitable", feature = "csv_export"))]
     #[error("CSV error: {0}")]
     CsvError(#[from] csv::Error),
+    /// Errors from SQLite.
+    #[cfg(feature = "sqlite")]
+    #[error("SQLite error: {0}")]
+    SqliteError(#[from] rusqlite::Error),
     /// Errors occurring during the parsing of templates or variable strings.
     #[error("Parse error: {0}")]
     ParseError(String),
+    /// Errors resolving a CLI command against an index (unknown or ambiguous command).
+    #[error("Command error: {0}")]
+    CommandError(String),
     /// Errors related to invalid states or state transitions.
     #[error("State error: {0}")]
     StateError(String),
@@ -1,5 +1,9 @@
-use crate::{DataRecord, TextFsmError};
+use crate::terminal::{self, TableStyle};
+use crate::xml::{element_name, element_names, escape};
+use crate::{DataRecord, LineRange, TextFsmError, Value};
+use serde::Serialize;
 use std::collections::BTreeSet;
+use std::io::Write;
 
 /// Supported output formats for parsed results.
 #[derive(Debug, Clone, Copy)]
@@ -7,6 +11,9 @@ pub enum OutputFormat {
     /// JSON format (using serde_json)
     #[cfg(feature = "json")]
     Json,
+    /// Newline-delimited JSON: one compact object per record
+    #[cfg(feature = "json")]
+    JsonLines,
     /// YAML format (using serde_yaml)
     #[cfg(feature = "yaml")]
     Yaml,
@@ -17,32 +24,164 @@ pub enum OutputFormat {
     Text,
     /// HTML table
     Html,
+    /// GitHub-flavoured Markdown pipe table
+    Markdown,
+    /// AsciiDoc table
+    AsciiDoc,
     /// XML format
     Xml,
+    /// Apache Parquet file (binary; use [`TextFsmExport::export_to`])
+    #[cfg(feature = "arrow")]
+    Parquet,
+}
+
+impl OutputFormat {
+    /// Returns `true` for formats that are not text, which
+    /// [`TextFsmExport::export`] cannot return as a `String`.
+    pub fn is_binary(&self) -> bool {
+        #[cfg(feature = "arrow")]
+        if let OutputFormat::Parquet = self {
+            return true;
+        }
+        false
+    }
+}
+
+/// How list values are rendered by the table-like formats, and the XML
+/// element names.
+///
+/// JSON, YAML and Parquet keep lists as lists. XML always writes an `<item>`
+/// element per entry and HTML a `<ul>` list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportOptions {
+    /// Joins list items in CSV, text, Markdown and AsciiDoc cells. Defaults to
+    /// `", "`.
+    pub list_separator: String,
+    /// Writes each list item of a text, Markdown or AsciiDoc table cell on its
+    /// own line instead of joining them.
+    pub multiline_lists: bool,
+    /// The XML root element. Defaults to `results`.
+    pub xml_root: String,
+    /// The XML element of each record. Defaults to `record`.
+    pub xml_record: String,
+    /// How text tables are drawn. Defaults to the plain table.
+    pub table: TableStyle,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        ExportOptions {
+            list_separator: ", ".to_string(),
+            multiline_lists: false,
+            xml_root: "results".to_string(),
+            xml_record: "record".to_string(),
+            table: TableStyle::default(),
+        }
+    }
+}
+
+impl ExportOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the separator joining list items.
+    pub fn with_list_separator(mut self, separator: &str) -> Self {
+        self.list_separator = separator.to_string();
+        self
+    }
+
+    /// Writes text table list items on separate lines.
+    pub fn with_multiline_lists(mut self, multiline: bool) -> Self {
+        self.multiline_lists = multiline;
+        self
+    }
+
+    /// Sets the XML root and record element names.
+    pub fn with_xml_tags(mut self, root: &str, record: &str) -> Self {
+        self.xml_root = root.to_string();
+        self.xml_record = record.to_string();
+        self
+    }
+
+    /// Sets how text tables are drawn.
+    pub fn with_table_style(mut self, table: TableStyle) -> Self {
+        self.table = table;
+        self
+    }
+
+    fn join(&self, value: &Value) -> String {
+        match value {
+            Value::Single(s) => s.clone(),
+            Value::List(l) => l.join(&self.list_separator),
+        }
+    }
+
+    /// The lines of a table cell; a list exploded over multiple lines has one
+    /// per item.
+    fn cell_lines(&self, value: Option<&Value>) -> Vec<String> {
+        match value {
+            Some(Value::List(l)) if self.multiline_lists => l.clone(),
+            Some(v) => self.join(v).lines().map(String::from).collect(),
+            None => vec![],
+        }
+    }
 }
 
 /// Trait to export parsing results to various formats.
 pub trait TextFsmExport {
     /// Exports the results to the specified format.
-    fn export(&self, format: OutputFormat) -> Result<String, TextFsmError>;
+    fn export(&self, format: OutputFormat) -> Result<String, TextFsmError> {
+        self.export_with(format, &ExportOptions::default())
+    }
+
+    /// Exports the results to the specified format with the given options.
+    fn export_with(
+        &self,
+        format: OutputFormat,
+        options: &ExportOptions,
+    ) -> Result<String, TextFsmError> {
+        if format.is_binary() {
+            return Err(TextFsmError::InternalError(format!(
+                "{:?} output is binary, use export_to",
+                format
+            )));
+        }
+        let mut out = vec![];
+        self.export_to_with(format, options, &mut out)?;
+        String::from_utf8(out).map_err(|e| TextFsmError::InternalError(e.to_string()))
+    }
+
+    /// Writes the results in the specified format to `writer`.
+    fn export_to(&self, format: OutputFormat, writer: &mut dyn Write) -> Result<(), TextFsmError> {
+        self.export_to_with(format, &ExportOptions::default(), writer)
+    }
+
+    /// Writes the results in the specified format to `writer` with the given options.
+    fn export_to_with(
+        &self,
+        format: OutputFormat,
+        options: &ExportOptions,
+        writer: &mut dyn Write,
+    ) -> Result<(), TextFsmError>;
 }
 
 impl TextFsmExport for Vec<DataRecord> {
-    fn export(&self, format: OutputFormat) -> Result<String, TextFsmError> {
-        match format {
-            #[cfg(feature = "json")]
-            OutputFormat::Json => serde_json::to_string_pretty(self)
-                .map_err(|e| TextFsmError::InternalError(e.to_string())),
-            #[cfg(feature = "yaml")]
-            OutputFormat::Yaml => {
-                serde_yaml::to_string(self).map_err(|e| TextFsmError::InternalError(e.to_string()))
-            }
-            #[cfg(feature = "csv_export")]
-            OutputFormat::Csv => export_csv(self),
-            OutputFormat::Text => export_text(self),
-            OutputFormat::Html => export_html(self),
-            OutputFormat::Xml => export_xml(self),
+    fn export_to_with(
+        &self,
+        format: OutputFormat,
+        options: &ExportOptions,
+        writer: &mut dyn Write,
+    ) -> Result<(), TextFsmError> {
+        let mut sink = RecordSink::new(writer, format)
+            .with_header(get_headers(self))
+            .with_options(options.clone());
+        for rec in self {
+            sink.write_record(rec)?;
         }
+        sink.finish()?;
+        Ok(())
     }
 }
 
@@ -56,150 +195,435 @@ fn get_headers(records: &[DataRecord]) -> Vec<String> {
     headers.into_iter().collect()
 }
 
-#[cfg(feature = "csv_export")]
-fn export_csv(records: &[DataRecord]) -> Result<String, TextFsmError> {
-    let headers = get_headers(records);
-    let mut wtr = csv::Writer::from_writer(vec![]);
-
-    // Write header
-    wtr.write_record(&headers)
-        .map_err(|e| TextFsmError::InternalError(e.to_string()))?;
+/// Where records come from, written around each record in JSON lines output
+/// and as attributes in XML output.
+///
+/// With metadata, every JSON line is an object holding the set fields and the
+/// record under `record`:
+///
+/// ```text
+/// {"template":"cisco_ios_show_version.textfsm","source":"r1.txt","lines":{"start":1,"end":12},"record":{...}}
+/// ```
+///
+/// In XML the fields are attributes of the root element, and the lines are
+/// `start_line` and `end_line` attributes of each record element.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RecordMetadata {
+    /// The template file name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// The platform, e.g. `cisco_ios`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    /// The command the output came from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// The file the input was read from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
 
-    // Write records
-    for rec in records {
-        let row: Vec<String> = headers
-            .iter()
-            .map(|h| {
-                if let Some(val) = rec.get(h) {
-                    val.to_string()
-                } else {
-                    String::new()
-                }
-            })
-            .collect();
-        wtr.write_record(&row)
-            .map_err(|e| TextFsmError::InternalError(e.to_string()))?;
-    }
+#[cfg(feature = "json")]
+#[derive(Serialize)]
+struct Envelope<'a> {
+    #[serde(flatten)]
+    metadata: &'a RecordMetadata,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lines: Option<LineRange>,
+    record: &'a DataRecord,
+}
 
-    let data = wtr
-        .into_inner()
-        .map_err(|e| TextFsmError::InternalError(e.to_string()))?;
-    String::from_utf8(data).map_err(|e| TextFsmError::InternalError(e.to_string()))
+/// Writes records one at a time as they are produced.
+///
+/// JSON, JSON lines, YAML, CSV, HTML, Markdown, AsciiDoc and XML output is
+/// written incrementally and matches what [`TextFsmExport::export`] produces
+/// for the same records. The text table needs every row to size its columns,
+/// and Parquet is written as a single row group, so both are written by
+/// [`finish`](Self::finish).
+///
+/// CSV, HTML, Markdown and AsciiDoc columns come from
+/// [`with_header`](Self::with_header), or from the sorted field names of the
+/// first record; fields outside them are not written. Text columns come from
+/// the header too, or from the sorted field names of all records.
+///
+/// ```ignore
+/// let mut sink = RecordSink::new(std::io::stdout().lock(), OutputFormat::Csv);
+/// for record in fsm.parse_reader(std::io::stdin().lock()) {
+///     sink.write_record(&record?)?;
+/// }
+/// sink.finish()?;
+/// ```
+pub struct RecordSink<W: Write> {
+    writer: W,
+    format: OutputFormat,
+    header: Option<Vec<String>>,
+    options: ExportOptions,
+    metadata: Option<RecordMetadata>,
+    #[cfg(feature = "arrow")]
+    arrow_schema: Option<arrow_schema::SchemaRef>,
+    count: usize,
+    started: bool,
+    buffered: Vec<DataRecord>,
 }
 
-fn export_html(records: &[DataRecord]) -> Result<String, TextFsmError> {
-    let headers = get_headers(records);
-    let mut html = String::from("<table>\n<thead>\n<tr>");
-    for h in &headers {
-        html.push_str(&format!("<th>{}</th>", h));
+impl<W: Write> RecordSink<W> {
+    /// Creates a sink writing `format` to `writer`.
+    pub fn new(writer: W, format: OutputFormat) -> Self {
+        RecordSink {
+            writer,
+            format,
+            header: None,
+            options: ExportOptions::default(),
+            metadata: None,
+            #[cfg(feature = "arrow")]
+            arrow_schema: None,
+            count: 0,
+            started: false,
+            buffered: vec![],
+        }
     }
-    html.push_str("</tr>\n</thead>\n<tbody>\n");
 
-    for rec in records {
-        html.push_str("<tr>");
-        for h in &headers {
-            let val = if let Some(v) = rec.get(h) {
-                let value_str = v.to_string();
-                value_str
-                    .replace('&', "&amp;")
-                    .replace('<', "&lt;")
-                    .replace('>', "&gt;")
-                    .replace('"', "&quot;")
-                    .replace('\'', "&apos;")
-            } else {
-                String::new()
-            };
-            html.push_str(&format!("<td>{}</td>", val));
-        }
-        html.push_str("</tr>\n");
+    /// Sets the CSV, text, HTML, Markdown and AsciiDoc columns.
+    pub fn with_header(mut self, header: Vec<String>) -> Self {
+        self.header = Some(header);
+        self
     }
-    html.push_str("</tbody>\n</table>");
-    Ok(html)
-}
 
-fn export_xml(records: &[DataRecord]) -> Result<String, TextFsmError> {
-    let headers = get_headers(records);
-    let mut xml = String::from("<results>\n");
+    /// Sets how list values are rendered.
+    pub fn with_options(mut self, options: ExportOptions) -> Self {
+        self.options = options;
+        self
+    }
 
-    for rec in records {
-        xml.push_str("  <record>\n");
-        for h in &headers {
-            if let Some(val) = rec.get(h) {
-                // Sanitize tag name (XML tags cannot contain spaces)
-                let tag_name = h.replace(' ', "_");
-                let value_str = val.to_string();
-                // Basic XML escaping
-                let escaped_value = value_str
-                    .replace('&', "&amp;")
-                    .replace('<', "&lt;")
-                    .replace('>', "&gt;")
-                    .replace('"', "&quot;")
-                    .replace('\'', "&apos;");
-                xml.push_str(&format!(
-                    "    <{}>{}</{}>\n",
-                    tag_name, escaped_value, tag_name
-                ));
-            }
-        }
-        xml.push_str("  </record>\n");
+    /// Wraps each JSON lines record with `metadata` and adds it to XML as
+    /// attributes; other formats ignore it.
+    pub fn with_metadata(mut self, metadata: RecordMetadata) -> Self {
+        self.metadata = Some(metadata);
+        self
     }
-    xml.push_str("</results>");
-    Ok(xml)
-}
 
-fn export_text(records: &[DataRecord]) -> Result<String, TextFsmError> {
-    let headers = get_headers(records);
-    if headers.is_empty() {
-        return Ok(String::new());
+    /// Sets the Parquet schema, e.g. from [`TextFSM::arrow_schema`](crate::TextFSM::arrow_schema).
+    /// Without it the schema is inferred from the records.
+    #[cfg(feature = "arrow")]
+    pub fn with_arrow_schema(mut self, schema: arrow_schema::SchemaRef) -> Self {
+        self.arrow_schema = Some(schema);
+        self
     }
 
-    // Calculate column widths
-    let mut widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+    /// The format being written.
+    pub fn format(&self) -> OutputFormat {
+        self.format
+    }
 
-    for rec in records {
-        for (i, h) in headers.iter().enumerate() {
-            if let Some(val) = rec.get(h) {
-                let len = val.to_string().len();
-                if len > widths[i] {
-                    widths[i] = len;
+    /// Number of records written so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    fn start(&mut self) -> Result<(), TextFsmError> {
+        if self.started {
+            return Ok(());
+        }
+        self.started = true;
+        let header = self.header.as_deref().unwrap_or_default();
+        match self.format {
+            #[cfg(feature = "csv_export")]
+            OutputFormat::Csv => write_csv_row(&mut self.writer, header)?,
+            OutputFormat::Html => {
+                write!(self.writer, "<table>\n<thead>\n<tr>")?;
+                for h in header.iter() {
+                    write!(self.writer, "<th>{}</th>", h)?;
                 }
+                writeln!(self.writer, "</tr>\n</thead>\n<tbody>")?;
             }
+            OutputFormat::Markdown if !header.is_empty() => {
+                let names: Vec<String> = header.iter().map(|h| markdown_escape(h)).collect();
+                let rule: Vec<String> = names.iter().map(|n| "-".repeat(n.len().max(3))).collect();
+                writeln!(self.writer, "| {} |", names.join(" | "))?;
+                writeln!(self.writer, "| {} |", rule.join(" | "))?;
+            }
+            OutputFormat::AsciiDoc if !header.is_empty() => {
+                let names: Vec<String> = header.iter().map(|h| asciidoc_escape(h)).collect();
+                writeln!(self.writer, "[options=\"header\"]\n|===")?;
+                writeln!(self.writer, "| {}\n", names.join(" | "))?;
+            }
+            OutputFormat::Xml => {
+                writeln!(self.writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+                write!(self.writer, "<{}", element_name(&self.options.xml_root))?;
+                if let Some(metadata) = &self.metadata {
+                    let attributes = [
+                        ("template", &metadata.template),
+                        ("platform", &metadata.platform),
+                        ("command", &metadata.command),
+                        ("source", &metadata.source),
+                    ];
+                    for (name, value) in attributes {
+                        if let Some(value) = value {
+                            write!(self.writer, " {}=\"{}\"", name, escape(value))?;
+                        }
+                    }
+                }
+                writeln!(self.writer, ">")?;
+            }
+            _ => {}
         }
+        Ok(())
     }
 
-    let mut out = String::new();
+    /// Writes one record.
+    pub fn write_record(&mut self, rec: &DataRecord) -> Result<(), TextFsmError> {
+        self.write_record_at(rec, None)
+    }
 
-    // Header
-    for (i, h) in headers.iter().enumerate() {
-        out.push_str(&format!("{:<width$}  ", h, width = widths[i]));
+    /// Writes one record captured from the given input lines. The lines are
+    /// only written with metadata.
+    pub fn write_record_at(
+        &mut self,
+        rec: &DataRecord,
+        lines: Option<LineRange>,
+    ) -> Result<(), TextFsmError> {
+        // Text and Parquet are buffered, so without a header they take their
+        // columns from all records when finished
+        let buffered = match self.format {
+            OutputFormat::Text => true,
+            #[cfg(feature = "arrow")]
+            OutputFormat::Parquet => true,
+            _ => false,
+        };
+        if self.header.is_none() && !buffered {
+            let mut header: Vec<String> = rec.fields.keys().cloned().collect();
+            header.sort();
+            self.header = Some(header);
+        }
+        self.start()?;
+        let header = self.header.as_deref().unwrap_or_default();
+        match self.format {
+            #[cfg(feature = "json")]
+            OutputFormat::Json => {
+                let json = serde_json::to_string_pretty(rec)
+                    .map_err(|e| TextFsmError::InternalError(e.to_string()))?;
+                let sep = if self.count == 0 { "[" } else { "," };
+                write!(self.writer, "{}\n  {}", sep, json.replace('\n', "\n  "))?;
+            }
+            #[cfg(feature = "json")]
+            OutputFormat::JsonLines => {
+                match &self.metadata {
+                    Some(metadata) => serde_json::to_writer(
+                        &mut self.writer,
+                        &Envelope {
+                            metadata,
+                            lines,
+                            record: rec,
+                        },
+                    ),
+                    None => serde_json::to_writer(&mut self.writer, rec),
+                }
+                .map_err(|e| TextFsmError::InternalError(e.to_string()))?;
+                writeln!(self.writer)?;
+            }
+            #[cfg(feature = "yaml")]
+            OutputFormat::Yaml => {
+                let yaml = serde_yaml::to_string(std::slice::from_ref(rec))
+                    .map_err(|e| TextFsmError::InternalError(e.to_string()))?;
+                self.writer.write_all(yaml.as_bytes())?;
+            }
+            #[cfg(feature = "csv_export")]
+            OutputFormat::Csv => {
+                let row: Vec<String> = header
+                    .iter()
+                    .map(|h| rec.get(h).map(|v| self.options.join(v)).unwrap_or_default())
+                    .collect();
+                write_csv_row(&mut self.writer, &row)?;
+            }
+            OutputFormat::Text => self.buffered.push(rec.clone()),
+            #[cfg(feature = "arrow")]
+            OutputFormat::Parquet => self.buffered.push(rec.clone()),
+            OutputFormat::Html => {
+                write!(self.writer, "<tr>")?;
+                for h in header {
+                    write!(self.writer, "<td>{}</td>", html_cell(rec.get(h)))?;
+                }
+                writeln!(self.writer, "</tr>")?;
+            }
+            OutputFormat::Markdown if !header.is_empty() => {
+                let cells: Vec<String> = header
+                    .iter()
+                    .map(|h| {
+                        let lines = self.options.cell_lines(rec.get(h));
+                        let lines: Vec<String> = lines.iter().map(|l| markdown_escape(l)).collect();
+                        lines.join("<br>")
+                    })
+                    .collect();
+                writeln!(self.writer, "| {} |", cells.join(" | "))?;
+            }
+            OutputFormat::AsciiDoc if !header.is_empty() => {
+                let cells: Vec<String> = header
+                    .iter()
+                    .map(|h| {
+                        let lines = self.options.cell_lines(rec.get(h));
+                        let lines: Vec<String> = lines.iter().map(|l| asciidoc_escape(l)).collect();
+                        lines.join(" +\n")
+                    })
+                    .collect();
+                let row = format!("| {}", cells.join(" | "));
+                writeln!(self.writer, "{}", row.trim_end())?;
+            }
+            OutputFormat::Markdown | OutputFormat::AsciiDoc => {}
+            OutputFormat::Xml => {
+                let record_tag = element_name(&self.options.xml_record);
+                write!(self.writer, "  <{}", record_tag)?;
+                if let (Some(_), Some(lines)) = (&self.metadata, lines) {
+                    write!(
+                        self.writer,
+                        " start_line=\"{}\" end_line=\"{}\"",
+                        lines.start, lines.end
+                    )?;
+                }
+                writeln!(self.writer, ">")?;
+                // Tags are unique across the header and the record's fields
+                let mut names: Vec<&str> = rec.fields.keys().map(String::as_str).collect();
+                names.sort_unstable();
+                let all: Vec<&str> = names
+                    .iter()
+                    .copied()
+                    .chain(header.iter().map(String::as_str))
+                    .collect();
+                let tags = element_names(&all);
+                for (name, tag) in names.into_iter().zip(tags) {
+                    // Keep the original of names that are not valid XML names
+                    let open = if tag == name {
+                        tag.to_string()
+                    } else {
+                        format!("{} name=\"{}\"", tag, escape(name))
+                    };
+                    match &rec.fields[name] {
+                        Value::Single(s) => {
+                            writeln!(self.writer, "    <{}>{}</{}>", open, escape(s), tag)?
+                        }
+                        Value::List(l) if l.is_empty() => {
+                            writeln!(self.writer, "    <{}></{}>", open, tag)?
+                        }
+                        Value::List(l) => {
+                            writeln!(self.writer, "    <{}>", open)?;
+                            for item in l {
+                                writeln!(self.writer, "      <item>{}</item>", escape(item))?;
+                            }
+                            writeln!(self.writer, "    </{}>", tag)?;
+                        }
+                    }
+                }
+                writeln!(self.writer, "  </{}>", record_tag)?;
+            }
+        }
+        self.count += 1;
+        Ok(())
     }
-    out.truncate(out.trim_end().len());
-    out.push('\n');
 
-    // Separator
-    for (i, _) in headers.iter().enumerate() {
-        out.push_str(&format!(
-            "{:<width$}  ",
-            "-".repeat(widths[i]),
-            width = widths[i]
-        ));
+    /// Writes what remains after the last record and returns the writer.
+    pub fn finish(mut self) -> Result<W, TextFsmError> {
+        self.start()?;
+        match self.format {
+            #[cfg(feature = "json")]
+            OutputFormat::Json if self.count == 0 => write!(self.writer, "[]")?,
+            #[cfg(feature = "json")]
+            OutputFormat::Json => write!(self.writer, "\n]")?,
+            #[cfg(feature = "yaml")]
+            OutputFormat::Yaml if self.count == 0 => writeln!(self.writer, "[]")?,
+            OutputFormat::Text => {
+                let text = export_text(self.header.as_deref(), &self.buffered, &self.options)?;
+                self.writer.write_all(text.as_bytes())?;
+            }
+            OutputFormat::Html => write!(self.writer, "</tbody>\n</table>")?,
+            OutputFormat::AsciiDoc if self.header.as_ref().is_some_and(|h| !h.is_empty()) => {
+                writeln!(self.writer, "|===")?
+            }
+            OutputFormat::Markdown | OutputFormat::AsciiDoc => {}
+            OutputFormat::Xml => {
+                write!(self.writer, "</{}>", element_name(&self.options.xml_root))?
+            }
+            #[cfg(feature = "arrow")]
+            OutputFormat::Parquet => {
+                let schema = match self.arrow_schema.take() {
+                    Some(schema) => schema,
+                    None => crate::arrow::infer_schema(&self.buffered),
+                };
+                let batch = crate::arrow::record_batch(&self.buffered, schema)?;
+                // The Parquet writer needs a `Send` writer
+                let data = crate::arrow::write_parquet(&batch, vec![])?;
+                self.writer.write_all(&data)?;
+            }
+            #[cfg(feature = "json")]
+            OutputFormat::JsonLines => {}
+            #[cfg(feature = "yaml")]
+            OutputFormat::Yaml => {}
+            #[cfg(feature = "csv_export")]
+            OutputFormat::Csv => {}
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
     }
-    out.truncate(out.trim_end().len());
-    out.push('\n');
+}
 
-    // Rows
-    for rec in records {
-        for (i, h) in headers.iter().enumerate() {
-            let val = if let Some(v) = rec.get(h) {
-                v.to_string()
-            } else {
-                String::new()
-            };
-            out.push_str(&format!("{:<width$}  ", val, width = widths[i]));
+/// Escapes a Markdown table cell: `|` would end the cell, and a trailing
+/// backslash would escape the cell's closing `|`.
+fn markdown_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+/// Escapes an AsciiDoc table cell: `|` would end the cell.
+fn asciidoc_escape(value: &str) -> String {
+    value.replace('|', "\\|")
+}
+
+#[cfg(feature = "csv_export")]
+fn write_csv_row<W: Write>(writer: &mut W, row: &[String]) -> Result<(), TextFsmError> {
+    let mut wtr = csv::Writer::from_writer(vec![]);
+    wtr.write_record(row)
+        .map_err(|e| TextFsmError::InternalError(e.to_string()))?;
+    let data = wtr
+        .into_inner()
+        .map_err(|e| TextFsmError::InternalError(e.to_string()))?;
+    writer.write_all(&data)?;
+    Ok(())
+}
+
+/// Renders a value as the content of an HTML table cell: lists as `<ul>`.
+pub(crate) fn html_cell(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::Single(s)) => escape(s),
+        Some(Value::List(l)) if !l.is_empty() => {
+            let items: String = l
+                .iter()
+                .map(|i| format!("<li>{}</li>", escape(i)))
+                .collect();
+            format!("<ul>{}</ul>", items)
         }
-        out.truncate(out.trim_end().len());
-        out.push('\n');
+        _ => String::new(),
     }
+}
+
+fn export_text(
+    header: Option<&[String]>,
+    records: &[DataRecord],
+    options: &ExportOptions,
+) -> Result<String, TextFsmError> {
+    let headers = match header {
+        Some(header) => header.to_vec(),
+        None => get_headers(records),
+    };
+    if headers.is_empty() {
+        return Ok(String::new());
+    }
+
+    let rows: Vec<Vec<Vec<String>>> = records
+        .iter()
+        .map(|rec| {
+            headers
+                .iter()
+                .map(|h| options.cell_lines(rec.get(h)))
+                .collect()
+        })
+        .collect();
 
-    Ok(out)
+    Ok(terminal::render(&headers, &rows, &options.table))
 }
@@ -0,0 +1,195 @@
+//! Reading exported results back into records.
+//!
+//! An [`Importer`] is the inverse of [`TextFsmExport`](crate::TextFsmExport)
+//! for the formats that keep every value: JSON, JSON lines, YAML and CSV. JSON
+//! and YAML keep lists as they were written. CSV cells are all text, so given
+//! the template ([`with_template`](Importer::with_template)) cells of `List`
+//! Values are split on the list separator again. Record keys are not exported,
+//! so they are rebuilt from the template's `Key` Values in every format. A
+//! `record_key` entry written by earlier releases is ignored.
+//!
+//! ```ignore
+//! let fsm = TextFSM::from_file("cisco_ios_show_vlan.textfsm")?;
+//! let golden = Importer::new(OutputFormat::Csv)
+//!     .with_template(&fsm, None)
+//!     .import_str(&std::fs::read_to_string("show_vlan.csv")?)?;
+//! let diff = diff_records(&golden, &current, &DiffOptions::default());
+//! ```
+
+use crate::export::{ExportOptions, OutputFormat};
+use crate::{DataRecord, DataRecordConversion, RecordKey, Result, TextFSM, TextFsmError, Value};
+use std::collections::HashSet;
+use std::io::Read;
+
+/// Reads records in one of the exported formats.
+#[derive(Debug, Clone)]
+pub struct Importer {
+    format: OutputFormat,
+    options: ExportOptions,
+    lists: HashSet<String>,
+    key: Vec<String>,
+}
+
+impl Importer {
+    /// Creates an importer for `format`, without a template: CSV cells are
+    /// read as single values and records get no key unless they carry one.
+    pub fn new(format: OutputFormat) -> Self {
+        Importer {
+            format,
+            options: ExportOptions::default(),
+            lists: HashSet::new(),
+            key: vec![],
+        }
+    }
+
+    /// Sets the options the results were exported with; the list separator
+    /// splits CSV cells of `List` Values.
+    pub fn with_options(mut self, options: ExportOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Restores `List` values and record keys from the Values of `fsm`, with
+    /// field names as its pipeline and `conversion` give them.
+    pub fn with_template(
+        mut self,
+        fsm: &TextFSM,
+        conversion: Option<DataRecordConversion>,
+    ) -> Self {
+        let pipeline = fsm.output_pipeline(conversion);
+        let lists: Vec<String> = fsm
+            .parser
+            .value_definitions()
+            .into_iter()
+            .filter(|v| v.is_list)
+            .map(|v| v.name.clone())
+            .collect();
+        self.lists = pipeline.header(&lists).into_iter().collect();
+        self.key = pipeline.header(&fsm.parser.key_names());
+        self
+    }
+
+    /// Reads the records in `input`.
+    pub fn import_str(&self, input: &str) -> Result<Vec<DataRecord>> {
+        let mut records = self.read(input)?;
+        for rec in records.iter_mut() {
+            self.restore(rec);
+        }
+        Ok(records)
+    }
+
+    /// Reads the records from `reader`.
+    pub fn import_from<R: Read>(&self, mut reader: R) -> Result<Vec<DataRecord>> {
+        let mut input = String::new();
+        reader.read_to_string(&mut input)?;
+        self.import_str(&input)
+    }
+
+    #[cfg_attr(
+        not(any(feature = "json", feature = "yaml", feature = "csv_export")),
+        allow(unused_variables)
+    )]
+    fn read(&self, input: &str) -> Result<Vec<DataRecord>> {
+        match self.format {
+            #[cfg(feature = "json")]
+            OutputFormat::Json => serde_json::from_str::<Vec<ImportedRecord>>(input)
+                .map(|records| records.into_iter().map(|r| r.record).collect())
+                .map_err(|e| TextFsmError::ParseError(format!("invalid JSON results: {}", e))),
+            #[cfg(feature = "json")]
+            OutputFormat::JsonLines => input
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| !line.trim().is_empty())
+                .map(|(i, line)| {
+                    read_json_line(line).map_err(|e| {
+                        TextFsmError::ParseError(format!("invalid JSON on line {}: {}", i + 1, e))
+                    })
+                })
+                .collect(),
+            #[cfg(feature = "yaml")]
+            OutputFormat::Yaml => read_yaml(input),
+            #[cfg(feature = "csv_export")]
+            OutputFormat::Csv => read_csv(input),
+            format => Err(TextFsmError::ParseError(format!(
+                "{:?} output cannot be imported",
+                format
+            ))),
+        }
+    }
+
+    /// Splits list cells and sets the record key.
+    fn restore(&self, rec: &mut DataRecord) {
+        for name in &self.lists {
+            if let Some(Value::Single(s)) = rec.fields.get(name) {
+                let items = if s.is_empty() {
+                    vec![]
+                } else {
+                    s.split(self.options.list_separator.as_str())
+                        .map(String::from)
+                        .collect()
+                };
+                rec.fields.insert(name.clone(), Value::List(items));
+            }
+        }
+        if rec.record_key.is_none() && !self.key.is_empty() {
+            rec.record_key = RecordKey::from_record(rec, &self.key);
+        }
+    }
+}
+
+/// A serialized record, without the `record_key` entry earlier releases wrote
+/// on every record (`null` for templates without `Key` Values).
+#[cfg(any(feature = "json", feature = "yaml"))]
+#[derive(serde::Deserialize)]
+struct ImportedRecord {
+    #[serde(default, rename = "record_key")]
+    _record_key: Option<serde::de::IgnoredAny>,
+    #[serde(flatten)]
+    record: DataRecord,
+}
+
+/// Reads a JSON line, unwrapping the record of a line written with metadata.
+#[cfg(feature = "json")]
+fn read_json_line(line: &str) -> serde_json::Result<DataRecord> {
+    let mut value: serde_json::Value = serde_json::from_str(line)?;
+    // Field values are strings or arrays, so an object under `record` is the
+    // record of a metadata envelope
+    if let Some(record) = value.get_mut("record").filter(|r| r.is_object()) {
+        value = record.take();
+    }
+    serde_json::from_value::<ImportedRecord>(value).map(|r| r.record)
+}
+
+/// Reads a YAML list of records, or an ntc-templates `parsed_sample` file.
+#[cfg(feature = "yaml")]
+fn read_yaml(input: &str) -> Result<Vec<DataRecord>> {
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum Document {
+        Records(Vec<ImportedRecord>),
+        ParsedSample { parsed_sample: Vec<ImportedRecord> },
+    }
+    let document: Document = serde_yaml::from_str(input)
+        .map_err(|e| TextFsmError::ParseError(format!("invalid YAML results: {}", e)))?;
+    let records = match document {
+        Document::Records(records) => records,
+        Document::ParsedSample { parsed_sample } => parsed_sample,
+    };
+    Ok(records.into_iter().map(|r| r.record).collect())
+}
+
+#[cfg(feature = "csv_export")]
+fn read_csv(input: &str) -> Result<Vec<DataRecord>> {
+    let mut reader = csv::Reader::from_reader(input.as_bytes());
+    let header: Vec<String> = reader.headers()?.iter().map(String::from).collect();
+    let mut records = vec![];
+    for row in reader.records() {
+        let mut rec = DataRecord::new();
+        for (name, cell) in header.iter().zip(row?.iter()) {
+            rec.fields
+                .insert(name.clone(), Value::Single(cell.to_string()));
+        }
+        records.push(rec);
+    }
+    Ok(records)
+}
@@ -8,14 +8,38 @@ use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
 use std::fmt;
+use std::sync::Arc;
 
+#[cfg(feature = "arrow")]
+pub mod arrow;
 #[cfg(feature = "clitable")]
 pub mod cli_table;
+pub mod diff;
+pub mod embed;
 pub mod export;
+pub mod import;
+#[cfg(feature = "normalize")]
+pub mod normalize;
+#[cfg(feature = "yaml")]
+pub mod regression;
+pub mod report;
+pub mod schema;
+pub mod snapshot;
+pub mod source;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod table;
+pub mod terminal;
+pub mod transform;
 pub mod varsubst;
+pub mod xml;
 #[cfg(feature = "clitable")]
 pub use cli_table::CliTable;
-pub use export::{OutputFormat, TextFsmExport};
+pub use export::{ExportOptions, OutputFormat, TextFsmExport};
+pub use source::TemplateSource;
+pub use table::ResultTable;
+pub use terminal::TableStyle;
+pub use transform::Pipeline;
 
 /// An iterator that parses input line-by-line and yields `DataRecord`s.
 pub struct TextFsmIter<R> {
@@ -25,13 +49,25 @@ pub struct TextFsmIter<R> {
     current_line: Option<String>,
 }
 
+impl<R> TextFsmIter<R> {
+    /// Returns the input lines of the record returned last by `next`.
+    pub fn last_lines(&self) -> Option<LineRange> {
+        self.fsm.last_lines
+    }
+
+    /// Returns the unmatched lines read so far, see [`TextFSM::with_unmatched_lines`].
+    pub fn unmatched_lines(&self) -> &[(usize, String)] {
+        &self.fsm.unmatched_lines
+    }
+}
+
 impl<R: std::io::BufRead> Iterator for TextFsmIter<R> {
     type Item = Result<DataRecord>;
 
     fn next(&mut self) -> Option<Self::Item> {
         // If we have accumulated records from previous lines (e.g. from 'Record' actions), return them first.
         if !self.fsm.records.is_empty() {
-            return Some(Ok(self.fsm.records.pop_front().unwrap()));
+            return self.fsm.pop_record();
         }
 
         if self.eof_processed {
@@ -43,7 +79,10 @@ impl<R: std::io::BufRead> Iterator for TextFsmIter<R> {
                 l.clone()
             } else {
                 match self.lines.next() {
-                    Some(Ok(l)) => l,
+                    Some(Ok(l)) => {
+                        self.fsm.line_number += 1;
+                        l
+                    }
                     Some(Err(e)) => return Some(Err(TextFsmError::IoError(e))),
                     None => {
                         // End of input. Handle EOF state transition logic.
@@ -56,7 +95,7 @@ impl<R: std::io::BufRead> Iterator for TextFsmIter<R> {
                             }
                             if !self.fsm.records.is_empty() {
                                 // Don't return None yet, next call will return None after popping records
-                                return Some(Ok(self.fsm.records.pop_front().unwrap()));
+                                return self.fsm.pop_record();
                             }
                             if let Err(e) = self.fsm.set_curr_state("End") {
                                 return Some(Err(e));
@@ -113,28 +152,138 @@ impl<R: std::io::BufRead> Iterator for TextFsmIter<R> {
             if self.fsm.curr_state == "EOF" || self.fsm.curr_state == "End" {
                 self.eof_processed = true;
                 if !self.fsm.records.is_empty() {
-                    return Some(Ok(self.fsm.records.pop_front().unwrap()));
+                    return self.fsm.pop_record();
                 }
                 return None; // Should break loop and return None
             }
 
             // If records were generated by this line, return the first one.
             if !self.fsm.records.is_empty() {
-                return Some(Ok(self.fsm.records.pop_front().unwrap()));
+                return self.fsm.pop_record();
+            }
+        }
+    }
+}
+
+/// The values of a record's `Key` fields, in template declaration order.
+///
+/// Serializes as a map from value name to value, e.g. `{"INTERFACE": "Gi0/1"}`,
+/// and displays as `INTERFACE=Gi0/1`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct RecordKey(pub Vec<(String, Value)>);
+
+impl RecordKey {
+    /// Builds the key of `record` from the named fields, in the given order.
+    ///
+    /// Returns `None` if the record lacks one of the fields.
+    pub fn from_record<S: AsRef<str>>(record: &DataRecord, names: &[S]) -> Option<Self> {
+        names
+            .iter()
+            .map(|n| {
+                let n = n.as_ref();
+                record.get(n).map(|v| (n.to_string(), v.clone()))
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(RecordKey)
+    }
+
+    /// Returns the value of one key field.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.0.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+
+    /// Returns an iterator over `(name, value)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.0.iter().map(|(n, v)| (n.as_str(), v))
+    }
+
+    /// Number of key fields.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the key has no fields.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for RecordKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (name, value)) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}={}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for RecordKey {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (name, value) in &self.0 {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for RecordKey {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        struct KeyVisitor;
+        impl<'de> serde::de::Visitor<'de> for KeyVisitor {
+            type Value = RecordKey;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a map of key value names to values")
+            }
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                mut access: A,
+            ) -> std::result::Result<RecordKey, A::Error> {
+                let mut fields: Vec<(String, Value)> = vec![];
+                while let Some(entry) = access.next_entry()? {
+                    fields.push(entry);
+                }
+                Ok(RecordKey(fields))
             }
         }
+        deserializer.deserialize_map(KeyVisitor)
     }
 }
 
+/// The 1-based, inclusive range of input lines a record was captured from.
+///
+/// It starts at the first line that captured a value into the record (or the
+/// line that recorded it, for records holding only filldown values) and ends
+/// at the line that recorded it.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct LineRange {
+    /// First line.
+    pub start: usize,
+    /// Last line.
+    pub end: usize,
+}
+
 /// Represents a single row of extracted data from a TextFSM template.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub struct DataRecord {
     /// Map of value names to their extracted values.
     #[serde(flatten)]
     pub fields: HashMap<String, Value>,
-    /// An optional key used to identify the record, constructed from fields marked as 'Key'.
-    #[serde(skip_deserializing)]
-    pub record_key: Option<String>,
+    /// The values of the fields marked as 'Key', set when the record is recorded.
+    /// `None` if the template declares no `Key` values. It repeats fields of
+    /// the record, so it is not serialized; an
+    /// [`Importer`](import::Importer) with the template rebuilds it.
+    #[serde(skip)]
+    pub record_key: Option<RecordKey>,
 }
 
 impl DataRecord {
@@ -143,6 +292,33 @@ impl DataRecord {
         Default::default()
     }
 
+    /// Computes the structured differences from `old` to `new` records.
+    ///
+    /// Records are aligned by `record_key` when every record has one, otherwise
+    /// by best match. See [`diff::diff_records`] for more control.
+    pub fn diff(old: &[Self], new: &[Self]) -> diff::RecordSetDiff {
+        diff::diff_records(old, new, &Default::default())
+    }
+
+    /// Indexes records by their `record_key`. Records without a key are skipped;
+    /// when several records share a key the last one wins.
+    pub fn index_by_key<I: IntoIterator<Item = DataRecord>>(
+        records: I,
+    ) -> HashMap<RecordKey, DataRecord> {
+        records
+            .into_iter()
+            .filter_map(|r| r.record_key.clone().map(|k| (k, r)))
+            .collect()
+    }
+
+    /// Same as [`index_by_key`](Self::index_by_key), borrowing the records.
+    pub fn index_refs_by_key(records: &[DataRecord]) -> HashMap<RecordKey, &DataRecord> {
+        records
+            .iter()
+            .filter_map(|r| r.record_key.clone().map(|k| (k, r)))
+            .collect()
+    }
+
     /// Overwrites existing fields in this record with fields from another record.
     pub fn overwrite_from(&mut self, from: DataRecord) {
         for (k, v) in from.fields {
@@ -152,6 +328,7 @@ impl DataRecord {
 
     /// Compares two sets of records and returns differences.
     /// Returns a tuple of (fields only in result, fields only in other).
+    #[deprecated(note = "records are compared by position; use `DataRecord::diff` instead")]
     pub fn compare_sets(result: &[Self], other: &[Self]) -> (Vec<Vec<String>>, Vec<Vec<String>>) {
         let mut only_in_result: Vec<Vec<String>> = vec![];
         let mut only_in_other: Vec<Vec<String>> = vec![];
@@ -259,7 +436,7 @@ impl DataRecord {
 }
 
 /// Represents an extracted value, which can be either a single string or a list of strings.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[serde(untagged)]
 pub enum Value {
     /// A single extracted string.
@@ -285,6 +462,8 @@ pub struct TextFSMParser {
     pub values: HashMap<String, ValueDefinition>,
     /// List of value names that are marked as 'Required'.
     pub mandatory_values: Vec<String>,
+    /// Names of all values in the order they are declared in the template.
+    pub value_names: Vec<String>,
     /// Compiled state machine states.
     pub states: HashMap<String, StateCompiled>,
 }
@@ -292,8 +471,8 @@ pub struct TextFSMParser {
 /// The runtime engine for TextFSM parsing.
 #[derive(Debug, Default, Clone)]
 pub struct TextFSM {
-    /// The underlying compiled parser.
-    pub parser: TextFSMParser,
+    /// The underlying compiled parser, shared with other engines built from it.
+    pub parser: Arc<TextFSMParser>,
     /// The current state of the engine.
     pub curr_state: String,
     /// The record currently being populated.
@@ -302,6 +481,26 @@ pub struct TextFSM {
     pub filldown_record: DataRecord,
     /// List of all successfully parsed records.
     pub records: VecDeque<DataRecord>,
+    /// Post-processing applied to records as they are returned.
+    pub pipeline: Pipeline,
+    /// If set, records with values of the wrong type are rejected.
+    pub schema: Option<schema::Schema>,
+    /// Number of input lines read so far.
+    pub line_number: usize,
+    /// The input lines of each record not returned yet, in the same order.
+    record_lines: VecDeque<LineRange>,
+    /// The input lines of each record returned by `parse_string`.
+    parsed_lines: Vec<LineRange>,
+    /// The line that started the record being populated.
+    record_start: Option<usize>,
+    /// The input lines of the record returned last by `pop_record`.
+    last_lines: Option<LineRange>,
+    /// If set, non-blank lines no rule matched are kept in `unmatched_lines`.
+    pub collect_unmatched: bool,
+    /// The number and text of the non-blank input lines no rule matched.
+    pub unmatched_lines: Vec<(usize, String)>,
+    /// The last line a rule matched.
+    last_matched: usize,
 }
 
 /// Action to take regarding the current line of input.
@@ -384,6 +583,48 @@ pub struct ValueDefinition {
     options: Option<String>,
 }
 
+impl ValueDefinition {
+    /// Name of the value.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the value is preserved across records until overwritten.
+    pub fn is_filldown(&self) -> bool {
+        self.is_filldown
+    }
+
+    /// Whether the value is part of the record's unique key.
+    pub fn is_key(&self) -> bool {
+        self.is_key
+    }
+
+    /// Whether a record must have the value populated to be valid.
+    pub fn is_required(&self) -> bool {
+        self.is_required
+    }
+
+    /// Whether the value collects multiple matches into a list.
+    pub fn is_list(&self) -> bool {
+        self.is_list
+    }
+
+    /// Whether the value is filled up into previous records.
+    pub fn is_fillup(&self) -> bool {
+        self.is_fillup
+    }
+
+    /// The regex pattern used to extract the value.
+    pub fn regex_pattern(&self) -> &str {
+        &self.regex_pattern
+    }
+
+    /// The raw options string, e.g. `Filldown,Required`.
+    pub fn options(&self) -> Option<&str> {
+        self.options.as_deref()
+    }
+}
+
 /// Wrapper for different regex engines (standard or fancy for lookarounds).
 #[derive(Debug, Clone)]
 pub enum MultiRegex {
@@ -426,6 +667,8 @@ pub struct StateCompiled {
 }
 
 /// Transformation options for extracted records.
+///
+/// A shorthand for common [`Pipeline`]s, applied after the parser's own pipeline.
 #[derive(Debug, Clone)]
 pub enum DataRecordConversion {
     /// Convert all field names to lowercase.
@@ -764,16 +1007,41 @@ impl TextFSMParser {
     ) -> Result<(HashMap<String, ValueDefinition>, Vec<String>)> {
         let mut vals = HashMap::new();
         let mut mandatory_values: Vec<String> = vec![];
+        for val in Self::parse_value_def_list(pair)? {
+            if val.is_required {
+                mandatory_values.push(val.name.clone());
+            }
+            vals.insert(val.name.clone(), val);
+        }
+        Ok((vals, mandatory_values))
+    }
+
+    /// Parses all value definitions, in declaration order.
+    pub fn parse_value_def_list(pair: &Pair<'_, Rule>) -> Result<Vec<ValueDefinition>> {
+        let mut vals: Vec<ValueDefinition> = vec![];
         for pair in pair.clone().into_inner() {
             if Rule::value_definition == pair.as_rule() {
-                let val = Self::parse_value_definition(&pair)?;
-                if val.is_required {
-                    mandatory_values.push(val.name.clone());
-                }
-                vals.insert(val.name.clone(), val);
+                vals.push(Self::parse_value_definition(&pair)?);
             }
         }
-        Ok((vals, mandatory_values))
+        Ok(vals)
+    }
+
+    /// Returns the value definitions in the order they are declared in the template.
+    pub fn value_definitions(&self) -> Vec<&ValueDefinition> {
+        self.value_names
+            .iter()
+            .filter_map(|name| self.values.get(name))
+            .collect()
+    }
+
+    /// Returns the names of the `Key` values in declaration order.
+    pub fn key_names(&self) -> Vec<String> {
+        self.value_definitions()
+            .into_iter()
+            .filter(|v| v.is_key)
+            .map(|v| v.name.clone())
+            .collect()
     }
 
     /// Parses and compiles a TextFSM template from a string.
@@ -789,6 +1057,7 @@ impl TextFSMParser {
         let mut values: HashMap<String, ValueDefinition> = HashMap::new();
         let mut states: HashMap<String, StateCompiled> = HashMap::new();
         let mut mandatory_values: Vec<String> = vec![];
+        let mut value_names: Vec<String> = vec![];
 
         let end_state = NextState::NamedState("End".to_string());
         let eof_rule = StateRule {
@@ -812,7 +1081,15 @@ impl TextFSMParser {
                 for pair in pairs.clone() {
                     match pair.as_rule() {
                         Rule::value_definitions => {
-                            (values, mandatory_values) = Self::parse_value_defs(&pair)?;
+                            for val in Self::parse_value_def_list(&pair)? {
+                                if val.is_required {
+                                    mandatory_values.push(val.name.clone());
+                                }
+                                if !value_names.contains(&val.name) {
+                                    value_names.push(val.name.clone());
+                                }
+                                values.insert(val.name.clone(), val);
+                            }
                         }
                         Rule::state_definitions => {
                             for pair in pair.clone().into_inner() {
@@ -868,6 +1145,7 @@ impl TextFSMParser {
                 Ok(TextFSMParser {
                     values,
                     mandatory_values,
+                    value_names,
                     states,
                 })
             }
@@ -882,12 +1160,25 @@ impl TextFSMParser {
         Self::from_string(&content)
             .map_err(|e| TextFsmError::ParseError(format!("file {} Error: {}", path.display(), e)))
     }
+
+    /// Parses and compiles a named template read from a `TemplateSource`.
+    pub fn from_source<S: TemplateSource + ?Sized>(source: &S, name: &str) -> Result<Self> {
+        let content = source.read_template(name)?;
+        Self::from_string(&content).map_err(|e| {
+            TextFsmError::ParseError(format!(
+                "template {} in {} Error: {}",
+                name,
+                source.location(),
+                e
+            ))
+        })
+    }
 }
 
 impl TextFSM {
     /// Creates a new `TextFSM` instance from a template string.
     pub fn from_string(content: &str) -> Result<Self> {
-        let parser = TextFSMParser::from_string(content)?;
+        let parser = Arc::new(TextFSMParser::from_string(content)?);
         let curr_state = "Start".to_string();
         Ok(TextFSM {
             parser,
@@ -898,7 +1189,7 @@ impl TextFSM {
 
     /// Creates a new `TextFSM` instance from a template file.
     pub fn from_file<P: AsRef<std::path::Path>>(fname: P) -> Result<Self> {
-        let parser = TextFSMParser::from_file(fname)?;
+        let parser = Arc::new(TextFSMParser::from_file(fname)?);
         let curr_state = "Start".to_string();
         Ok(TextFSM {
             parser,
@@ -907,6 +1198,26 @@ impl TextFSM {
         })
     }
 
+    /// Creates a new `TextFSM` instance from a named template in a `TemplateSource`.
+    pub fn from_source<S: TemplateSource + ?Sized>(source: &S, name: &str) -> Result<Self> {
+        Ok(Self::from_parser(TextFSMParser::from_source(source, name)?))
+    }
+
+    /// Creates a new `TextFSM` instance from an already compiled template.
+    pub fn from_parser(parser: TextFSMParser) -> Self {
+        Self::from_shared(Arc::new(parser))
+    }
+
+    /// Creates a new `TextFSM` instance sharing a compiled template, such as
+    /// one from the `CliTable` template cache, without copying it.
+    pub fn from_shared(parser: Arc<TextFSMParser>) -> Self {
+        TextFSM {
+            parser,
+            curr_state: "Start".to_string(),
+            ..Default::default()
+        }
+    }
+
     /// Resets the engine to its initial state, clearing all records and resetting variables.
     /// This allows reusing the parsed template for a new file.
     pub fn reset(&mut self) {
@@ -914,6 +1225,13 @@ impl TextFSM {
         self.curr_record = DataRecord::default();
         self.filldown_record = DataRecord::default();
         self.records.clear();
+        self.line_number = 0;
+        self.record_lines.clear();
+        self.parsed_lines.clear();
+        self.record_start = None;
+        self.last_lines = None;
+        self.unmatched_lines.clear();
+        self.last_matched = 0;
     }
 
     /// Sets the current state of the engine.
@@ -981,16 +1299,6 @@ impl TextFSM {
 
         curr_record.fields.insert(name.clone(), ins_value.clone());
 
-        if var_info.is_key {
-            curr_record.record_key = if let Some(k) = curr_record.record_key.as_ref() {
-                Some(format!("{}/{:?}", k, &ins_value))
-            } else {
-                Some(format!("{:?}", &ins_value))
-            };
-
-            trace!("RECORD KEY: '{:?}'", &curr_record.record_key);
-        }
-
         if var_info.is_filldown {
             filldown_record.fields.insert(name.clone(), ins_value);
         }
@@ -1004,6 +1312,7 @@ impl TextFSM {
         records: &mut VecDeque<DataRecord>,
         mandatory_values: &[String],
         values: &HashMap<String, ValueDefinition>,
+        value_names: &[String],
         action: RecordAction,
     ) -> Result<()> {
         match action {
@@ -1034,6 +1343,13 @@ impl TextFSM {
                                 }
                             }
                         }
+                        let key_names: Vec<&String> = value_names
+                            .iter()
+                            .filter(|n| values.get(*n).is_some_and(|v| v.is_key))
+                            .collect();
+                        if !key_names.is_empty() {
+                            new_rec.record_key = RecordKey::from_record(&new_rec, &key_names);
+                        }
                         trace!("RECORD: {:?}", &new_rec);
                         records.push_back(new_rec);
                     } else {
@@ -1092,7 +1408,6 @@ impl TextFSM {
                 trace!("TRY RULE: {:?}", &rule);
                 let mut capture_matched = false;
                 tmp_datarec.fields.clear();
-                tmp_datarec.record_key = None;
                 tmp_filldown_rec.fields.clear();
                 fillup_fields.clear();
 
@@ -1143,8 +1458,12 @@ impl TextFSM {
                     }
                 }
                 if capture_matched {
+                    self.last_matched = self.line_number;
                     trace!("TMP_REC: {:?}", &tmp_datarec);
                     trace!("TMP_FILLDOWN: {:?}", &tmp_filldown_rec);
+                    if !tmp_datarec.fields.is_empty() && self.record_start.is_none() {
+                        self.record_start = Some(self.line_number);
+                    }
                     for (name, v) in tmp_datarec.fields.drain() {
                         if fillup_fields.contains(&name) {
                             let name_ref = &name;
@@ -1168,8 +1487,6 @@ impl TextFSM {
                         }
                         self.curr_record.append_value(name, v);
                     }
-                    trace!("TMP KEY: {:?}", &tmp_datarec.record_key);
-                    self.curr_record.record_key = tmp_datarec.record_key;
                     // The below is incorrect:
                     // self.filldown_record.overwrite_from(tmp_filldown_rec);
                     // This is correct:
@@ -1180,14 +1497,26 @@ impl TextFSM {
                 }
                 // println!("TRANS: {:?}", &transition);
 
+                let record_count = self.records.len();
+                let record_action = transition.record_action.clone();
                 Self::process_record_action(
                     &mut self.curr_record,
                     &mut self.filldown_record,
                     &mut self.records,
                     &self.parser.mandatory_values,
                     &self.parser.values,
+                    &self.parser.value_names,
                     transition.record_action,
                 )?;
+                if self.records.len() > record_count {
+                    let end = self.line_number;
+                    self.record_lines.push_back(LineRange {
+                        start: self.record_start.take().unwrap_or(end).min(end),
+                        end,
+                    });
+                } else if matches!(record_action, RecordAction::Clear | RecordAction::Clearall) {
+                    self.record_start = None;
+                }
 
                 match transition.line_action {
                     LineAction::Next(x) => return Ok(ParseStatus::NextLine(x)),
@@ -1204,16 +1533,74 @@ impl TextFSM {
                 &self.curr_state
             )));
         }
+        // A line matched in the state it continued from is not unmatched
+        if self.collect_unmatched
+            && self.last_matched != self.line_number
+            && self.curr_state != "EOF"
+            && !aline.trim().is_empty()
+        {
+            self.unmatched_lines
+                .push((self.line_number, aline.to_string()));
+        }
         Ok(ParseStatus::NextLine(None))
     }
 
+    /// Sets the post-processing applied to every parsed record.
+    pub fn with_pipeline(mut self, pipeline: Pipeline) -> Self {
+        self.pipeline = pipeline;
+        self
+    }
+
+    fn output_pipeline(&self, conversion: Option<DataRecordConversion>) -> Pipeline {
+        self.pipeline.clone().extend(conversion.into())
+    }
+
+    /// Keeps the non-blank input lines that no rule matched in
+    /// [`unmatched_lines`](Self::unmatched_lines), e.g. to spot output a
+    /// template does not cover.
+    pub fn with_unmatched_lines(mut self) -> Self {
+        self.collect_unmatched = true;
+        self
+    }
+
+    /// Checks the captured values of every record against `schema`, see
+    /// [`Schema::check_record`](schema::Schema::check_record).
+    pub fn with_schema(mut self, schema: schema::Schema) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    fn check_record(&self, record: &DataRecord) -> Result<()> {
+        match &self.schema {
+            Some(schema) => schema.check_record(record),
+            None => Ok(()),
+        }
+    }
+
+    /// Removes the oldest parsed record and returns it after the pipeline.
+    fn pop_record(&mut self) -> Option<Result<DataRecord>> {
+        let record = self.records.pop_front()?;
+        self.last_lines = self.record_lines.pop_front();
+        Some(
+            self.check_record(&record)
+                .map(|_| self.pipeline.apply(record)),
+        )
+    }
+
     /// Returns a new vector of records with all field names converted to lowercase.
     pub fn lowercase_keys(src: &VecDeque<DataRecord>) -> Vec<DataRecord> {
         let mut out = vec![];
 
         for irec in src {
             let mut hm = DataRecord::new();
-            hm.record_key = irec.record_key.clone();
+            hm.record_key = irec.record_key.as_ref().map(|key| {
+                RecordKey(
+                    key.0
+                        .iter()
+                        .map(|(n, v)| (n.to_lowercase(), v.clone()))
+                        .collect(),
+                )
+            });
             for (k, v) in irec.iter() {
                 let kl = k.to_lowercase();
                 hm.fields.insert(kl, v.clone());
@@ -1225,6 +1612,7 @@ impl TextFSM {
 
     /// Parses input from a reader line-by-line using an iterator.
     /// This is memory efficient for large files as it doesn't buffer all records.
+    /// Records are passed through the parser's [`Pipeline`] as they are yielded.
     ///
     /// # Arguments
     /// * `reader` - A type implementing `BufRead` (e.g., `BufReader<File>`).
@@ -1247,8 +1635,9 @@ impl TextFSM {
         input: &str,
         conversion: Option<DataRecordConversion>,
     ) -> Result<Vec<DataRecord>> {
-        for (_lineno, aline) in input.lines().enumerate() {
-            debug!("LINE:#{}: '{}'", _lineno + 1, &aline);
+        for aline in input.lines() {
+            self.line_number += 1;
+            debug!("LINE:#{}: '{}'", self.line_number, &aline);
             loop {
                 let status = self.parse_line(aline)?;
                 match status {
@@ -1295,10 +1684,13 @@ impl TextFSM {
             // FIXME: Can EOF state transition into something else ? Presumably not.
             self.set_curr_state("End")?;
         }
-        match conversion {
-            None => Ok(self.records.clone().into()),
-            Some(DataRecordConversion::LowercaseKeys) => Ok(Self::lowercase_keys(&self.records)),
+        // The records are returned now, so their lines are no longer pending
+        self.parsed_lines = self.record_lines.drain(..).collect();
+        for record in &self.records {
+            self.check_record(record)?;
         }
+        let pipeline = self.output_pipeline(conversion);
+        Ok(pipeline.apply_all(self.records.iter().cloned()))
     }
 
     /// Parses an entire file and returns the extracted records.
@@ -1314,4 +1706,40 @@ impl TextFSM {
         let input = std::fs::read_to_string(fname)?;
         self.parse_string(&input, conversion)
     }
+
+    /// Returns the input lines of each record returned by the last
+    /// [`parse_string`](Self::parse_string) or [`parse_file`](Self::parse_file),
+    /// in the same order.
+    pub fn parsed_lines(&self) -> &[LineRange] {
+        &self.parsed_lines
+    }
+
+    /// Returns the table header: the Value names in declaration order, renamed by
+    /// the pipeline and `conversion`.
+    pub fn header(&self, conversion: Option<DataRecordConversion>) -> Vec<String> {
+        self.output_pipeline(conversion)
+            .header(&self.parser.value_names)
+    }
+
+    /// Same as [`parse_string`](Self::parse_string), returning a [`ResultTable`]
+    /// carrying the template's header.
+    pub fn parse_string_table(
+        &mut self,
+        input: &str,
+        conversion: Option<DataRecordConversion>,
+    ) -> Result<ResultTable> {
+        let records = self.parse_string(input, conversion.clone())?;
+        Ok(ResultTable::new(self.header(conversion), records))
+    }
+
+    /// Same as [`parse_file`](Self::parse_file), returning a [`ResultTable`]
+    /// carrying the template's header.
+    pub fn parse_file_table<P: AsRef<std::path::Path>>(
+        &mut self,
+        fname: P,
+        conversion: Option<DataRecordConversion>,
+    ) -> Result<ResultTable> {
+        let input = std::fs::read_to_string(fname)?;
+        self.parse_string_table(&input, conversion)
+    }
 }
@@ -0,0 +1,589 @@
+//! Canonical forms for common network data types.
+//!
+//! The same data arrives in different shapes across vendors: `Gi0/1` and
+//! `GigabitEthernet0/1`, MAC addresses as `aabb.ccdd.eeff` or `aa-bb-cc-dd-ee-ff`,
+//! uptimes as `1 week, 2 days, 3 hours` or `1w2d`, speeds as `1000Mb/s` or `1 Gbps`.
+//! The functions here return a canonical form, or `None` if the input is not
+//! recognized. A [`Normalizer`] plugs them into a [`Pipeline`]:
+//!
+//! ```ignore
+//! let pipeline = Pipeline::new()
+//!     .normalize("INTERFACE", Normalizer::interface("cisco_ios"))
+//!     .normalize("UPTIME", Normalizer::Duration)
+//!     .normalize_common("cisco_ios");
+//! ```
+
+use crate::transform::Pipeline;
+use crate::{DataRecord, Value};
+use std::net::{IpAddr, Ipv4Addr};
+
+/// Interface name abbreviations, `(abbreviation, full name)`. The first
+/// abbreviation listed for a full name is the preferred short form.
+type InterfaceTable = &'static [(&'static str, &'static str)];
+
+const IOS_INTERFACES: InterfaceTable = &[
+    ("Gi", "GigabitEthernet"),
+    ("Fa", "FastEthernet"),
+    ("Te", "TenGigabitEthernet"),
+    ("Tw", "TwoGigabitEthernet"),
+    ("Twe", "TwentyFiveGigE"),
+    ("Fo", "FortyGigabitEthernet"),
+    ("Hu", "HundredGigE"),
+    ("Et", "Ethernet"),
+    ("Eth", "Ethernet"),
+    ("Po", "Port-channel"),
+    ("Lo", "Loopback"),
+    ("Vl", "Vlan"),
+    ("Tu", "Tunnel"),
+    ("Se", "Serial"),
+    ("Nu", "Null"),
+    ("BDI", "BDI"),
+];
+
+const NXOS_INTERFACES: InterfaceTable = &[
+    ("Eth", "Ethernet"),
+    ("Et", "Ethernet"),
+    ("Po", "port-channel"),
+    ("Lo", "loopback"),
+    ("Vlan", "Vlan"),
+    ("Tunnel", "Tunnel"),
+    ("mgmt", "mgmt"),
+    ("nve", "nve"),
+];
+
+const EOS_INTERFACES: InterfaceTable = &[
+    ("Et", "Ethernet"),
+    ("Eth", "Ethernet"),
+    ("Po", "Port-Channel"),
+    ("Ma", "Management"),
+    ("Lo", "Loopback"),
+    ("Vl", "Vlan"),
+    ("Vx", "Vxlan"),
+    ("Tu", "Tunnel"),
+];
+
+const XR_INTERFACES: InterfaceTable = &[
+    ("Gi", "GigabitEthernet"),
+    ("Te", "TenGigE"),
+    ("TF", "TwentyFiveGigE"),
+    ("Fo", "FortyGigE"),
+    ("Hu", "HundredGigE"),
+    ("BE", "Bundle-Ether"),
+    ("Mg", "MgmtEth"),
+    ("Lo", "Loopback"),
+    ("tt", "tunnel-te"),
+    ("Nu", "Null"),
+];
+
+const VRP_INTERFACES: InterfaceTable = &[
+    ("GE", "GigabitEthernet"),
+    ("XGE", "XGigabitEthernet"),
+    ("25GE", "25GE"),
+    ("40GE", "40GE"),
+    ("100GE", "100GE"),
+    ("Eth-Trunk", "Eth-Trunk"),
+    ("Vlanif", "Vlanif"),
+    ("MEth", "MEth"),
+    ("Loop", "LoopBack"),
+    ("NULL", "NULL"),
+];
+
+fn interface_table(platform: &str) -> InterfaceTable {
+    match platform {
+        "cisco_nxos" => NXOS_INTERFACES,
+        "arista_eos" => EOS_INTERFACES,
+        "cisco_xr" => XR_INTERFACES,
+        p if p.starts_with("huawei") => VRP_INTERFACES,
+        _ => IOS_INTERFACES,
+    }
+}
+
+/// Splits an interface name into its type and the rest, e.g. `Gi` and `0/1`.
+fn split_interface(name: &str) -> (&str, &str) {
+    let digits = name.len() - name.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let type_len = name[digits..]
+        .find(|c: char| !(c.is_ascii_alphabetic() || c == '-'))
+        .map(|i| i + digits)
+        .unwrap_or(name.len());
+    (&name[..type_len], name[type_len..].trim_start())
+}
+
+/// Returns the full interface type for `kind` on a platform.
+fn full_interface_type(kind: &str, table: InterfaceTable) -> Option<&'static str> {
+    if let Some((_, full)) = table
+        .iter()
+        .find(|(abbr, full)| full.eq_ignore_ascii_case(kind) || abbr.eq_ignore_ascii_case(kind))
+    {
+        return Some(full);
+    }
+    // An unambiguous prefix of a full name, e.g. `Gig` or `TenGig`.
+    let lower = kind.to_ascii_lowercase();
+    let mut candidates: Vec<&'static str> = table
+        .iter()
+        .map(|(_, full)| *full)
+        .filter(|full| full.to_ascii_lowercase().starts_with(&lower))
+        .collect();
+    candidates.dedup();
+    match candidates[..] {
+        [full] if kind.len() >= 2 => Some(full),
+        _ => None,
+    }
+}
+
+/// Expands an interface name to its full form for a platform (an ntc-templates
+/// platform name such as `cisco_ios` or `arista_eos`), e.g. `Gi0/1` to
+/// `GigabitEthernet0/1`. Returns `None` for unknown interface types.
+pub fn interface_name(name: &str, platform: &str) -> Option<String> {
+    let (kind, rest) = split_interface(name.trim());
+    if kind.is_empty() || rest.is_empty() {
+        return None;
+    }
+    let full = full_interface_type(kind, interface_table(platform))?;
+    Some(format!("{}{}", full, rest))
+}
+
+/// Shortens an interface name to its usual abbreviation for a platform, e.g.
+/// `GigabitEthernet0/1` to `Gi0/1`. Returns `None` for unknown interface types.
+pub fn interface_abbreviation(name: &str, platform: &str) -> Option<String> {
+    let table = interface_table(platform);
+    let (kind, rest) = split_interface(name.trim());
+    if kind.is_empty() || rest.is_empty() {
+        return None;
+    }
+    let full = full_interface_type(kind, table)?;
+    let (abbr, _) = table.iter().find(|(_, f)| *f == full)?;
+    Some(format!("{}{}", abbr, rest))
+}
+
+/// Output format for MAC addresses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MacFormat {
+    /// `aa:bb:cc:dd:ee:ff`
+    #[default]
+    Colon,
+    /// `aa-bb-cc-dd-ee-ff`
+    Dash,
+    /// `aabb.ccdd.eeff`
+    Dot,
+    /// `aabbccddeeff`
+    Bare,
+}
+
+/// Formats a MAC address given with `:`, `-` or `.` separators, or none, in
+/// lowercase `format`.
+pub fn mac_address(mac: &str, format: MacFormat) -> Option<String> {
+    let hex: String = mac
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, ':' | '-' | '.'))
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    if hex.len() != 12 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let groups = |size: usize, sep: &str| {
+        (0..12)
+            .step_by(size)
+            .map(|i| &hex[i..i + size])
+            .collect::<Vec<_>>()
+            .join(sep)
+    };
+    Some(match format {
+        MacFormat::Colon => groups(2, ":"),
+        MacFormat::Dash => groups(2, "-"),
+        MacFormat::Dot => groups(4, "."),
+        MacFormat::Bare => hex,
+    })
+}
+
+fn duration_unit(unit: &str) -> Option<u64> {
+    const DAY: u64 = 86_400;
+    Some(match unit {
+        "y" | "yr" | "yrs" | "year" | "years" => 365 * DAY,
+        "mo" | "month" | "months" => 30 * DAY,
+        "w" | "wk" | "wks" | "week" | "weeks" => 7 * DAY,
+        "d" | "day" | "days" => DAY,
+        "h" | "hr" | "hrs" | "hour" | "hours" => 3_600,
+        "m" | "min" | "mins" | "minute" | "minutes" => 60,
+        "s" | "sec" | "secs" | "second" | "seconds" => 1,
+        _ => return None,
+    })
+}
+
+/// Parses a duration into seconds. Accepts `1 week, 2 days, 3 hours`,
+/// `1w2d`, `2d03h`, `01:02:03`, `3d 04:05:06` and a bare number of seconds.
+/// Durations too long for a `u64` give `None`.
+pub fn duration_seconds(duration: &str) -> Option<u64> {
+    let text = duration.trim().to_ascii_lowercase();
+    if let Ok(secs) = text.parse::<u64>() {
+        return Some(secs);
+    }
+    let mut total = 0u64;
+    let mut parsed_any = false;
+    let mut rest = text.as_str();
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        if let Some(r) = rest.strip_prefix("and ") {
+            rest = r;
+            continue;
+        }
+        if rest.is_empty() {
+            break;
+        }
+        let num_len = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if num_len == 0 {
+            return None;
+        }
+        if rest[num_len..].starts_with(':') {
+            // A time of day, `hh:mm` or `hh:mm:ss`.
+            let time_len = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == ':'))
+                .unwrap_or(rest.len());
+            let parts: Vec<u64> = rest[..time_len]
+                .split(':')
+                .map(|p| p.parse().ok())
+                .collect::<Option<_>>()?;
+            let (h, m, s) = match parts[..] {
+                [h, m] => (h, m, 0),
+                [h, m, s] => (h, m, s),
+                _ => return None,
+            };
+            let secs = h
+                .checked_mul(3_600)?
+                .checked_add(m.checked_mul(60)?)?
+                .checked_add(s)?;
+            total = total.checked_add(secs)?;
+            rest = &rest[time_len..];
+        } else {
+            let value: u64 = rest[..num_len].parse().ok()?;
+            rest = rest[num_len..].trim_start();
+            let unit_len = rest
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(rest.len());
+            let secs = value.checked_mul(duration_unit(&rest[..unit_len])?)?;
+            total = total.checked_add(secs)?;
+            rest = &rest[unit_len..];
+        }
+        parsed_any = true;
+    }
+    parsed_any.then_some(total)
+}
+
+/// Parses a bit or byte rate into bits per second. Accepts `1000Mb/s`,
+/// `1 Gbps`, `10G`, `100 Mbit`, `1000000 bits/sec` and byte rates such as
+/// `12 MB/s` or `5 kBps`. A bare number is in bits per second, except the Cisco
+/// auto-negotiated form `a-1000`, which is in Mb/s.
+pub fn rate_bps(rate: &str) -> Option<u64> {
+    let text = rate.trim();
+    if let Some(mbps) = text.strip_prefix("a-") {
+        return mbps
+            .parse::<u64>()
+            .ok()
+            .and_then(|m| m.checked_mul(1_000_000));
+    }
+    let num_len = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let value: f64 = text[..num_len].parse().ok()?;
+    let unit = text[num_len..].trim();
+
+    let (multiplier, unit) = match unit.chars().next() {
+        Some(c @ ('k' | 'K' | 'M' | 'G' | 'T')) => {
+            let multiplier = match c {
+                'k' | 'K' => 1e3,
+                'M' => 1e6,
+                'G' => 1e9,
+                _ => 1e12,
+            };
+            (multiplier, &unit[1..])
+        }
+        _ => (1.0, unit),
+    };
+    let lower = unit.to_ascii_lowercase();
+    let bits_per_unit = if unit.is_empty() {
+        1.0
+    } else if unit.starts_with('B') || lower.starts_with("byte") {
+        match lower.as_str() {
+            "b" | "bps" | "b/s" | "b/sec" | "byte" | "bytes" | "byte/s" | "bytes/s"
+            | "bytes/sec" => 8.0,
+            _ => return None,
+        }
+    } else {
+        match lower.as_str() {
+            "b" | "bps" | "b/s" | "b/sec" | "bit" | "bits" | "bit/s" | "bits/s" | "bit/sec"
+            | "bits/sec" => 1.0,
+            _ => return None,
+        }
+    };
+    Some((value * multiplier * bits_per_unit).round() as u64)
+}
+
+fn parse_ip(addr: &str) -> Option<IpAddr> {
+    let addr = addr.trim();
+    if let Ok(ip) = addr.parse::<IpAddr>() {
+        return Some(ip);
+    }
+    // IPv4 with leading zeros, e.g. `010.000.000.001`.
+    let octets: Vec<u8> = addr
+        .split('.')
+        .map(|o| o.parse().ok())
+        .collect::<Option<_>>()?;
+    match octets[..] {
+        [a, b, c, d] => Some(IpAddr::V4(Ipv4Addr::new(a, b, c, d))),
+        _ => None,
+    }
+}
+
+/// Formats an IPv4 or IPv6 address canonically: IPv4 without leading zeros,
+/// IPv6 compressed and lowercase.
+pub fn ip_address(addr: &str) -> Option<String> {
+    parse_ip(addr).map(|ip| ip.to_string())
+}
+
+/// Converts a contiguous dotted-quad netmask to a prefix length, e.g.
+/// `255.255.255.0` to 24.
+pub fn netmask_to_prefix_len(mask: &str) -> Option<u8> {
+    let mask: Ipv4Addr = mask.trim().parse().ok()?;
+    let bits = u32::from(mask);
+    (bits.leading_ones() + bits.trailing_zeros() == 32).then_some(bits.leading_ones() as u8)
+}
+
+/// Converts a prefix length to a dotted-quad netmask, e.g. 24 to `255.255.255.0`.
+pub fn prefix_len_to_netmask(len: u8) -> Option<String> {
+    (len <= 32).then(|| {
+        let bits = u32::MAX.checked_shl(32 - len as u32).unwrap_or(0);
+        Ipv4Addr::from(bits).to_string()
+    })
+}
+
+/// Formats an address with a prefix length or netmask as `address/length`.
+/// Accepts `10.0.0.0/8`, `10.0.0.0 255.0.0.0`, `10.0.0.0/255.0.0.0` and IPv6
+/// prefixes.
+pub fn prefix(value: &str) -> Option<String> {
+    let value = value.trim();
+    let (addr, len) = value
+        .split_once('/')
+        .or_else(|| value.split_once(char::is_whitespace))?;
+    let ip = parse_ip(addr)?;
+    let max = if ip.is_ipv4() { 32 } else { 128 };
+    let len = match len.trim().parse::<u8>() {
+        Ok(len) if len <= max => len,
+        Ok(_) => return None,
+        Err(_) if ip.is_ipv4() => netmask_to_prefix_len(len)?,
+        Err(_) => return None,
+    };
+    Some(format!("{}/{}", ip, len))
+}
+
+/// A canonicalizer for the values of a field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Normalizer {
+    /// Full interface names for a platform, see [`interface_name`].
+    Interface { platform: String },
+    /// MAC addresses, see [`mac_address`].
+    MacAddress(MacFormat),
+    /// Durations as a number of seconds, see [`duration_seconds`].
+    Duration,
+    /// Rates as bits per second, see [`rate_bps`].
+    Rate,
+    /// IP addresses, see [`ip_address`].
+    IpAddress,
+    /// Prefixes as `address/length`, see [`prefix`].
+    Prefix,
+}
+
+impl Normalizer {
+    /// Full interface names for a platform.
+    pub fn interface(platform: &str) -> Self {
+        Normalizer::Interface {
+            platform: platform.to_string(),
+        }
+    }
+
+    /// Returns the canonical form of `value`, or `None` if it is not recognized.
+    pub fn normalize(&self, value: &str) -> Option<String> {
+        match self {
+            Normalizer::Interface { platform } => interface_name(value, platform),
+            Normalizer::MacAddress(format) => mac_address(value, *format),
+            Normalizer::Duration => duration_seconds(value).map(|s| s.to_string()),
+            Normalizer::Rate => rate_bps(value).map(|b| b.to_string()),
+            Normalizer::IpAddress => ip_address(value),
+            Normalizer::Prefix => prefix(value),
+        }
+    }
+
+    /// Normalizes a value in place; items that are not recognized are left as they are.
+    pub fn apply(&self, value: &mut Value) {
+        let items = match value {
+            Value::Single(s) => std::slice::from_mut(s),
+            Value::List(l) => l.as_mut_slice(),
+        };
+        for item in items {
+            if let Some(normalized) = self.normalize(item) {
+                *item = normalized;
+            }
+        }
+    }
+
+    /// The normalizers for the common capture group names of
+    /// `docs/common_capture_groups.md` on a platform.
+    pub fn common(platform: &str) -> Vec<(&'static str, Normalizer)> {
+        let mut out = vec![];
+        for field in [
+            "INTERFACE",
+            "LOCAL_INTERFACE",
+            "MEMBER_INTERFACE",
+            "BUNDLE_NAME",
+        ] {
+            out.push((field, Normalizer::interface(platform)));
+        }
+        for field in ["MAC_ADDRESS", "BIA"] {
+            out.push((field, Normalizer::MacAddress(MacFormat::default())));
+        }
+        for field in [
+            "IP_ADDRESS",
+            "IP_ADDRESSES",
+            "IP_HELPER",
+            "GATEWAY",
+            "IPV6_ADDRESS",
+            "IPV6_ADDRESSES",
+            "IPV6_GATEWAY",
+            "LOCAL_IP_ADDRESS",
+            "MGMT_IP_ADDRESS",
+        ] {
+            out.push((field, Normalizer::IpAddress));
+        }
+        out
+    }
+}
+
+impl Pipeline {
+    /// Normalizes the values of `field`, matched case-insensitively.
+    pub fn normalize(self, field: &str, normalizer: Normalizer) -> Self {
+        let field = field.to_string();
+        self.map(move |record: &mut DataRecord| {
+            for (name, value) in record.fields.iter_mut() {
+                if name.eq_ignore_ascii_case(&field) {
+                    normalizer.apply(value);
+                }
+            }
+        })
+    }
+
+    /// Normalizes the common capture groups, see [`Normalizer::common`].
+    pub fn normalize_common(self, platform: &str) -> Self {
+        Normalizer::common(platform)
+            .into_iter()
+            .fold(self, |p, (field, normalizer)| {
+                p.normalize(field, normalizer)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interface_name() {
+        assert_eq!(
+            interface_name("Gi0/1", "cisco_ios").as_deref(),
+            Some("GigabitEthernet0/1")
+        );
+        assert_eq!(
+            interface_name("gigabitethernet0/1", "cisco_ios").as_deref(),
+            Some("GigabitEthernet0/1")
+        );
+        assert_eq!(
+            interface_name("TenGig1/0/1", "cisco_ios").as_deref(),
+            Some("TenGigabitEthernet1/0/1")
+        );
+        assert_eq!(
+            interface_name("Po10", "cisco_nxos").as_deref(),
+            Some("port-channel10")
+        );
+        assert_eq!(
+            interface_name("Po10", "arista_eos").as_deref(),
+            Some("Port-Channel10")
+        );
+        assert_eq!(
+            interface_name("XGE0/0/1", "huawei_vrp").as_deref(),
+            Some("XGigabitEthernet0/0/1")
+        );
+        assert_eq!(interface_name("ge-0/0/0", "juniper_junos"), None);
+        assert_eq!(interface_name("Gi", "cisco_ios"), None);
+        assert_eq!(
+            interface_abbreviation("Bundle-Ether1", "cisco_xr").as_deref(),
+            Some("BE1")
+        );
+    }
+
+    #[test]
+    fn test_mac_address() {
+        for mac in ["aabb.ccdd.eeff", "AA:BB:CC:DD:EE:FF", "aa-bb-cc-dd-ee-ff"] {
+            assert_eq!(
+                mac_address(mac, MacFormat::Colon).as_deref(),
+                Some("aa:bb:cc:dd:ee:ff")
+            );
+        }
+        assert_eq!(
+            mac_address("aa:bb:cc:dd:ee:ff", MacFormat::Dot).as_deref(),
+            Some("aabb.ccdd.eeff")
+        );
+        assert_eq!(mac_address("aa:bb:cc:dd:ee", MacFormat::Colon), None);
+        assert_eq!(mac_address("gg:bb:cc:dd:ee:ff", MacFormat::Colon), None);
+    }
+
+    #[test]
+    fn test_duration_seconds() {
+        assert_eq!(
+            duration_seconds("1 week, 2 days, 3 hours"),
+            Some(7 * 86_400 + 2 * 86_400 + 3 * 3_600)
+        );
+        assert_eq!(duration_seconds("1w2d"), Some(9 * 86_400));
+        assert_eq!(duration_seconds("2d03h"), Some(2 * 86_400 + 3 * 3_600));
+        assert_eq!(duration_seconds("01:02:03"), Some(3_723));
+        assert_eq!(duration_seconds("3d 00:00:10"), Some(3 * 86_400 + 10));
+        assert_eq!(
+            duration_seconds("2 hours and 5 minutes"),
+            Some(2 * 3_600 + 300)
+        );
+        assert_eq!(duration_seconds("42"), Some(42));
+        assert_eq!(duration_seconds("never"), None);
+        assert_eq!(duration_seconds("3 fortnights"), None);
+    }
+
+    #[test]
+    fn test_rate_bps() {
+        assert_eq!(rate_bps("1000Mb/s"), Some(1_000_000_000));
+        assert_eq!(rate_bps("1 Gbps"), Some(1_000_000_000));
+        assert_eq!(rate_bps("10G"), Some(10_000_000_000));
+        assert_eq!(rate_bps("2.5 Gbit"), Some(2_500_000_000));
+        assert_eq!(rate_bps("1000000 bits/sec"), Some(1_000_000));
+        assert_eq!(rate_bps("12 MB/s"), Some(96_000_000));
+        assert_eq!(rate_bps("a-1000"), Some(1_000_000_000));
+        assert_eq!(rate_bps("auto"), None);
+        assert_eq!(rate_bps("10 Mfoo"), None);
+    }
+
+    #[test]
+    fn test_ip_and_prefix() {
+        assert_eq!(ip_address("010.000.000.001").as_deref(), Some("10.0.0.1"));
+        assert_eq!(
+            ip_address("2001:DB8:0:0::1").as_deref(),
+            Some("2001:db8::1")
+        );
+        assert_eq!(prefix("10.0.0.0 255.0.0.0").as_deref(), Some("10.0.0.0/8"));
+        assert_eq!(
+            prefix("192.0.2.1/255.255.255.0").as_deref(),
+            Some("192.0.2.1/24")
+        );
+        assert_eq!(prefix("2001:DB8::/32").as_deref(), Some("2001:db8::/32"));
+        assert_eq!(prefix("10.0.0.0/33"), None);
+        assert_eq!(prefix("10.0.0.0 255.0.255.0"), None);
+        assert_eq!(prefix_len_to_netmask(0).as_deref(), Some("0.0.0.0"));
+        assert_eq!(prefix_len_to_netmask(20).as_deref(), Some("255.255.240.0"));
+    }
+}
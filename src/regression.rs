@@ -0,0 +1,520 @@
+//! Regression testing of templates against sample outputs.
+//!
+//! The runner understands the ntc-templates test layout:
+//!
+//! ```text
+//! <templates_dir>/<platform>_<command>.textfsm
+//! <tests_dir>/<platform>/<command>/<name>.raw   raw device output
+//! <tests_dir>/<platform>/<command>/<name>.yml   expected `parsed_sample`
+//! ```
+//!
+//! Every `.raw` file is a test case. It is parsed with the matching template
+//! (field names lowercased, as in ntc-templates) and compared with the records
+//! listed under `parsed_sample` in the `.yml` file next to it, using
+//! [`diff_records`] aligned on the template's `Key` Values. By default the order
+//! of records does not matter. A [`RegressionReport`] can be rendered as
+//! JUnit XML for CI, and [`CaseResult::bless`] rewrites the expected file from
+//! the current output.
+
+use crate::diff::{DiffOptions, RecordSetDiff, diff_records};
+use crate::xml::escape;
+use crate::{
+    DataRecord, DataRecordConversion, Result, TextFSM, TextFSMParser, TextFsmError, Value,
+};
+use log::debug;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+#[derive(Deserialize)]
+struct ParsedSample {
+    #[serde(default)]
+    parsed_sample: Vec<DataRecord>,
+}
+
+/// One raw sample together with its template and expected output.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TestCase {
+    /// Platform directory name, e.g. `cisco_ios`.
+    pub platform: String,
+    /// Command directory name, e.g. `show_version`.
+    pub command: String,
+    /// File stem of the sample, e.g. `cisco_ios_show_version2`.
+    pub name: String,
+    /// Template used to parse the sample.
+    pub template: PathBuf,
+    /// The raw device output.
+    pub raw: PathBuf,
+    /// The expected output; it may not exist yet.
+    pub expected: PathBuf,
+}
+
+impl TestCase {
+    /// Returns `platform/command/name`.
+    pub fn id(&self) -> String {
+        format!("{}/{}/{}", self.platform, self.command, self.name)
+    }
+}
+
+/// Outcome of a single test case.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CaseStatus {
+    /// The output matches the expected records.
+    Passed,
+    /// The output differs from the expected records; holds the differences from
+    /// the expected (old) to the actual (new) records.
+    Failed(RecordSetDiff),
+    /// The case could not be run: missing template or expected file, parse error...
+    Error(String),
+}
+
+/// The result of running one [`TestCase`].
+#[derive(Debug, Clone)]
+pub struct CaseResult {
+    /// The case that was run.
+    pub case: TestCase,
+    /// Whether it passed.
+    pub status: CaseStatus,
+    /// Time spent parsing and comparing.
+    pub duration: Duration,
+    /// The parsed records, if parsing succeeded.
+    pub actual: Option<Vec<DataRecord>>,
+    /// Lowercased Value names of the template in declaration order.
+    pub header: Vec<String>,
+}
+
+impl CaseResult {
+    /// Returns `true` if the case passed.
+    pub fn passed(&self) -> bool {
+        self.status == CaseStatus::Passed
+    }
+
+    /// Rewrites the expected file from the current output.
+    ///
+    /// Does nothing and returns `false` when the case passed or could not be parsed.
+    pub fn bless(&self) -> Result<bool> {
+        let Some(actual) = &self.actual else {
+            return Ok(false);
+        };
+        if self.passed() {
+            return Ok(false);
+        }
+        std::fs::write(&self.case.expected, to_parsed_sample(actual, &self.header))?;
+        debug!("Blessed {}", self.case.expected.display());
+        Ok(true)
+    }
+}
+
+impl fmt::Display for CaseResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.status {
+            CaseStatus::Passed => write!(f, "PASS {}", self.case.id()),
+            CaseStatus::Error(msg) => write!(f, "ERROR {}: {}", self.case.id(), msg),
+            CaseStatus::Failed(diff) => write!(
+                f,
+                "FAIL {}\n  {}",
+                self.case.id(),
+                diff.to_unified().replace('\n', "\n  ")
+            ),
+        }
+    }
+}
+
+/// Results of a whole regression run.
+#[derive(Debug, Clone, Default)]
+pub struct RegressionReport {
+    /// Per-case results, sorted by case.
+    pub results: Vec<CaseResult>,
+}
+
+impl RegressionReport {
+    /// Number of cases that passed.
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|r| r.passed()).count()
+    }
+
+    /// Number of cases whose output differs from the expected records.
+    pub fn failed(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|r| matches!(r.status, CaseStatus::Failed(_)))
+            .count()
+    }
+
+    /// Number of cases that could not be run.
+    pub fn errors(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|r| matches!(r.status, CaseStatus::Error(_)))
+            .count()
+    }
+
+    /// Returns `true` if every case passed.
+    pub fn is_success(&self) -> bool {
+        self.passed() == self.results.len()
+    }
+
+    /// Renders the report as JUnit XML, one `<testsuite>` per platform.
+    pub fn to_junit(&self) -> String {
+        let total_time: f64 = self.results.iter().map(|r| r.duration.as_secs_f64()).sum();
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str(&format!(
+            "<testsuites name=\"textfsm\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\">\n",
+            self.results.len(),
+            self.failed(),
+            self.errors(),
+            total_time
+        ));
+
+        let mut suites: BTreeMap<&str, Vec<&CaseResult>> = BTreeMap::new();
+        for result in &self.results {
+            suites
+                .entry(&result.case.platform)
+                .or_default()
+                .push(result);
+        }
+        for (platform, results) in suites {
+            let count =
+                |f: fn(&CaseStatus) -> bool| results.iter().filter(|r| f(&r.status)).count();
+            xml.push_str(&format!(
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\">\n",
+                escape(platform),
+                results.len(),
+                count(|s| matches!(s, CaseStatus::Failed(_))),
+                count(|s| matches!(s, CaseStatus::Error(_))),
+                results.iter().map(|r| r.duration.as_secs_f64()).sum::<f64>()
+            ));
+            for result in results {
+                let case = &result.case;
+                xml.push_str(&format!(
+                    "    <testcase classname=\"{}.{}\" name=\"{}\" time=\"{:.3}\"",
+                    escape(&case.platform),
+                    escape(&case.command),
+                    escape(&case.name),
+                    result.duration.as_secs_f64()
+                ));
+                match &result.status {
+                    CaseStatus::Passed => xml.push_str("/>\n"),
+                    CaseStatus::Failed(diff) => {
+                        xml.push_str(&format!(
+                            ">\n      <failure message=\"{} record difference(s)\">{}</failure>\n    </testcase>\n",
+                            diff.changes.len(),
+                            escape(&diff.to_unified())
+                        ));
+                    }
+                    CaseStatus::Error(msg) => {
+                        xml.push_str(&format!(
+                            ">\n      <error message=\"{}\"/>\n    </testcase>\n",
+                            escape(msg)
+                        ));
+                    }
+                }
+            }
+            xml.push_str("  </testsuite>\n");
+        }
+        xml.push_str("</testsuites>\n");
+        xml
+    }
+}
+
+impl fmt::Display for RegressionReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} case(s): {} passed, {} failed, {} error(s)",
+            self.results.len(),
+            self.passed(),
+            self.failed(),
+            self.errors()
+        )
+    }
+}
+
+/// Discovers and runs regression test cases.
+#[derive(Debug, Clone)]
+pub struct RegressionRunner {
+    /// Directory holding `<platform>_<command>.textfsm` templates.
+    pub templates_dir: PathBuf,
+    /// Root of the `<platform>/<command>/<name>.raw` tree.
+    pub tests_dir: PathBuf,
+    /// Also fail when records appear in a different order than expected.
+    pub ordered: bool,
+    /// Only run cases whose `platform/command/name` contains this string.
+    pub filter: Option<String>,
+}
+
+impl RegressionRunner {
+    /// Creates a runner for explicit template and test directories.
+    pub fn new<P: AsRef<Path>, Q: AsRef<Path>>(templates_dir: P, tests_dir: Q) -> Self {
+        RegressionRunner {
+            templates_dir: templates_dir.as_ref().to_path_buf(),
+            tests_dir: tests_dir.as_ref().to_path_buf(),
+            ordered: false,
+            filter: None,
+        }
+    }
+
+    /// Creates a runner for an ntc-templates checkout
+    /// (`ntc_templates/templates` and `tests` below `root`).
+    pub fn from_checkout<P: AsRef<Path>>(root: P) -> Self {
+        let root = root.as_ref();
+        Self::new(
+            root.join("ntc_templates").join("templates"),
+            root.join("tests"),
+        )
+    }
+
+    /// Finds all test cases below `tests_dir`, sorted.
+    pub fn discover(&self) -> Result<Vec<TestCase>> {
+        let mut cases: Vec<TestCase> = vec![];
+        for platform_dir in sorted_dirs(&self.tests_dir)? {
+            let platform = dir_name(&platform_dir);
+            for command_dir in sorted_dirs(&platform_dir)? {
+                let command = dir_name(&command_dir);
+                let template = self
+                    .templates_dir
+                    .join(format!("{}_{}.textfsm", platform, command));
+                for entry in std::fs::read_dir(&command_dir)? {
+                    let raw = entry?.path();
+                    if !raw.is_file() || raw.extension().and_then(|e| e.to_str()) != Some("raw") {
+                        continue;
+                    }
+                    let Some(name) = raw.file_stem().and_then(|n| n.to_str()) else {
+                        continue;
+                    };
+                    let case = TestCase {
+                        platform: platform.clone(),
+                        command: command.clone(),
+                        name: name.to_string(),
+                        template: template.clone(),
+                        expected: raw.with_extension("yml"),
+                        raw,
+                    };
+                    if let Some(filter) = &self.filter
+                        && !case.id().contains(filter.as_str())
+                    {
+                        continue;
+                    }
+                    cases.push(case);
+                }
+            }
+        }
+        cases.sort();
+        debug!(
+            "Discovered {} test cases in {}",
+            cases.len(),
+            self.tests_dir.display()
+        );
+        Ok(cases)
+    }
+
+    /// Runs every discovered test case.
+    pub fn run(&self) -> Result<RegressionReport> {
+        let mut parsers: HashMap<PathBuf, std::result::Result<TextFSMParser, String>> =
+            HashMap::new();
+        let results = self
+            .discover()?
+            .into_iter()
+            .map(|case| {
+                let parser = parsers.entry(case.template.clone()).or_insert_with(|| {
+                    TextFSMParser::from_file(&case.template).map_err(|e| e.to_string())
+                });
+                self.run_with(case, parser)
+            })
+            .collect();
+        Ok(RegressionReport { results })
+    }
+
+    /// Runs a single test case.
+    pub fn run_case(&self, case: &TestCase) -> CaseResult {
+        let parser = TextFSMParser::from_file(&case.template).map_err(|e| e.to_string());
+        self.run_with(case.clone(), &parser)
+    }
+
+    fn run_with(
+        &self,
+        case: TestCase,
+        parser: &std::result::Result<TextFSMParser, String>,
+    ) -> CaseResult {
+        let start = Instant::now();
+        let mut result = CaseResult {
+            case,
+            status: CaseStatus::Passed,
+            duration: Duration::ZERO,
+            actual: None,
+            header: vec![],
+        };
+        result.status = match self.check(&mut result, parser) {
+            Ok(status) => status,
+            Err(e) => CaseStatus::Error(e.to_string()),
+        };
+        result.duration = start.elapsed();
+        result
+    }
+
+    fn check(
+        &self,
+        result: &mut CaseResult,
+        parser: &std::result::Result<TextFSMParser, String>,
+    ) -> Result<CaseStatus> {
+        let case = &result.case;
+        let parser = match parser {
+            Ok(parser) => parser.clone(),
+            Err(e) => {
+                return Ok(CaseStatus::Error(format!(
+                    "template {}: {}",
+                    case.template.display(),
+                    e
+                )));
+            }
+        };
+        result.header = parser
+            .value_names
+            .iter()
+            .map(|n| n.to_lowercase())
+            .collect();
+        let options = DiffOptions {
+            key_fields: parser
+                .key_names()
+                .iter()
+                .map(|n| n.to_lowercase())
+                .collect(),
+            report_moves: self.ordered,
+        };
+        let mut fsm = TextFSM::from_parser(parser);
+        let actual = fsm.parse_file(&case.raw, Some(DataRecordConversion::LowercaseKeys))?;
+        result.actual = Some(actual);
+
+        if !case.expected.exists() {
+            return Ok(CaseStatus::Error(format!(
+                "no expected output {}",
+                case.expected.display()
+            )));
+        }
+        let expected = std::fs::read_to_string(&case.expected)?;
+        let expected: ParsedSample = serde_yaml::from_str(&expected)
+            .map_err(|e| TextFsmError::ParseError(format!("{}: {}", case.expected.display(), e)))?;
+
+        let diff = diff_records(
+            &expected.parsed_sample,
+            result.actual.as_deref().unwrap_or_default(),
+            &options,
+        );
+        if diff.is_empty() {
+            Ok(CaseStatus::Passed)
+        } else {
+            Ok(CaseStatus::Failed(diff))
+        }
+    }
+}
+
+fn sorted_dirs(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut dirs: Vec<PathBuf> = vec![];
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() && !dir_name(&path).starts_with('.') {
+            dirs.push(path);
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+fn dir_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn yaml_quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Renders records as an ntc-templates style `parsed_sample` YAML document.
+///
+/// Fields listed in `header` come first in that order, any others follow sorted.
+pub fn to_parsed_sample(records: &[DataRecord], header: &[String]) -> String {
+    let mut yaml = String::from("---\nparsed_sample:");
+    if records.is_empty() {
+        yaml.push_str(" []\n");
+        return yaml;
+    }
+    yaml.push('\n');
+    for record in records {
+        let mut names: Vec<&String> = header
+            .iter()
+            .filter(|h| record.fields.contains_key(*h))
+            .collect();
+        let mut others: Vec<&String> = record
+            .fields
+            .keys()
+            .filter(|k| !header.contains(k))
+            .collect();
+        others.sort();
+        names.extend(others);
+
+        for (i, name) in names.iter().enumerate() {
+            yaml.push_str(if i == 0 { "  - " } else { "    " });
+            match &record.fields[*name] {
+                Value::Single(s) => yaml.push_str(&format!("{}: {}\n", name, yaml_quote(s))),
+                Value::List(l) if l.is_empty() => yaml.push_str(&format!("{}: []\n", name)),
+                Value::List(l) => {
+                    yaml.push_str(&format!("{}:\n", name));
+                    for item in l {
+                        yaml.push_str(&format!("      - {}\n", yaml_quote(item)));
+                    }
+                }
+            }
+        }
+        if names.is_empty() {
+            yaml.push_str("  - {}\n");
+        }
+    }
+    yaml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rec(fields: &[(&str, &str)]) -> DataRecord {
+        let mut r = DataRecord::new();
+        for (k, v) in fields {
+            r.insert(k.to_string(), v.to_string());
+        }
+        r
+    }
+
+    #[test]
+    fn test_to_parsed_sample() {
+        let mut r = rec(&[("name", "a \"b\""), ("extra", "x")]);
+        r.append_value(
+            "ports".to_string(),
+            Value::List(vec!["1".into(), "2".into()]),
+        );
+        let yaml = to_parsed_sample(&[r.clone()], &["name".to_string(), "ports".to_string()]);
+        assert_eq!(
+            yaml,
+            "---\nparsed_sample:\n  - name: \"a \\\"b\\\"\"\n    ports:\n      - \"1\"\n      - \"2\"\n    extra: \"x\"\n"
+        );
+        let back: ParsedSample = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(back.parsed_sample[0].fields, r.fields);
+        assert_eq!(to_parsed_sample(&[], &[]), "---\nparsed_sample: []\n");
+    }
+}
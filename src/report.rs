@@ -0,0 +1,286 @@
+//! Self-contained HTML reports of parse results.
+//!
+//! An [`HtmlReport`] is a complete HTML document with one table per
+//! [`ReportSection`], e.g. one per command parsed from a device. Styles and the
+//! script that sorts (click a column header) and filters (type in the box
+//! above a table) the rows are inlined, so the file can be mailed or attached
+//! to a ticket and opened without network access. Each section shows its
+//! template, platform, command and source, and the input lines no rule
+//! matched, if they were collected.
+//!
+//! ```ignore
+//! let mut fsm = TextFSM::from_file("cisco_ios_show_version.textfsm")?.with_unmatched_lines();
+//! let section = ReportSection::parse(&mut fsm, &input, None)?.with_title("show version");
+//! let report = HtmlReport::new("r1 state").with_section(section);
+//! std::fs::write("r1.html", report.render()?)?;
+//! ```
+
+use crate::export::{RecordMetadata, html_cell};
+use crate::xml::escape;
+use crate::{DataRecord, DataRecordConversion, Result, ResultTable, TextFSM};
+use std::io::Write;
+
+const STYLE: &str = r#"body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.6em; }
+h2 { font-size: 1.25em; margin-top: 2em; border-bottom: 1px solid #ddd; padding-bottom: .2em; }
+nav ul { padding-left: 1.2em; }
+dl.meta { display: grid; grid-template-columns: max-content auto; gap: .2em 1em; margin: .5em 0; }
+dl.meta dt { font-weight: bold; }
+dl.meta dd { margin: 0; font-family: monospace; }
+input.filter { margin: .5em 0; padding: .3em; width: 20em; }
+table { border-collapse: collapse; font-size: .9em; }
+th, td { border: 1px solid #ccc; padding: .3em .6em; text-align: left; vertical-align: top; }
+th { background: #f0f3f7; cursor: pointer; user-select: none; white-space: nowrap; }
+th[data-order="asc"]::after { content: " \25B2"; }
+th[data-order="desc"]::after { content: " \25BC"; }
+tbody tr:nth-child(even) { background: #fafafa; }
+td ul { margin: 0; padding-left: 1.2em; }
+p.empty { color: #666; font-style: italic; }
+details.unmatched { margin: .8em 0; padding: .4em .8em; background: #fff8e1; border-left: 4px solid #f0a500; }
+details.unmatched pre { margin: .4em 0; white-space: pre-wrap; }
+"#;
+
+const SCRIPT: &str = r#"document.querySelectorAll("section").forEach(function (section) {
+  var table = section.querySelector("table");
+  if (!table) return;
+  var body = table.tBodies[0];
+  section.querySelector("input.filter").addEventListener("input", function (e) {
+    var text = e.target.value.toLowerCase();
+    Array.prototype.forEach.call(body.rows, function (row) {
+      row.hidden = text !== "" && row.textContent.toLowerCase().indexOf(text) < 0;
+    });
+  });
+  table.querySelectorAll("th").forEach(function (th, column) {
+    th.addEventListener("click", function () {
+      var order = th.dataset.order === "asc" ? "desc" : "asc";
+      table.querySelectorAll("th").forEach(function (other) { delete other.dataset.order; });
+      th.dataset.order = order;
+      var rows = Array.prototype.slice.call(body.rows);
+      rows.sort(function (a, b) {
+        var x = a.cells[column].textContent, y = b.cells[column].textContent;
+        var c = x !== "" && y !== "" && !isNaN(x) && !isNaN(y)
+          ? x - y
+          : x.localeCompare(y, undefined, { numeric: true });
+        return order === "asc" ? c : -c;
+      });
+      rows.forEach(function (row) { body.appendChild(row); });
+    });
+  });
+});
+"#;
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+/// One table of a report.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReportSection {
+    /// The heading; defaults to the command or template.
+    pub title: Option<String>,
+    /// Where the records come from.
+    pub metadata: RecordMetadata,
+    /// The columns.
+    pub header: Vec<String>,
+    /// The records, one per row.
+    pub records: Vec<DataRecord>,
+    /// The number and text of input lines no rule matched.
+    pub unmatched: Vec<(usize, String)>,
+}
+
+impl ReportSection {
+    /// Creates a section from a header and records.
+    pub fn new(header: Vec<String>, records: Vec<DataRecord>) -> Self {
+        ReportSection {
+            header,
+            records,
+            ..Default::default()
+        }
+    }
+
+    /// Parses `input` with `fsm` into a section with the template's header
+    /// and the unmatched lines, if `fsm` collects them.
+    pub fn parse(
+        fsm: &mut TextFSM,
+        input: &str,
+        conversion: Option<DataRecordConversion>,
+    ) -> Result<Self> {
+        let records = fsm.parse_string(input, conversion.clone())?;
+        let mut section = Self::new(fsm.header(conversion), records);
+        section.unmatched = std::mem::take(&mut fsm.unmatched_lines);
+        Ok(section)
+    }
+
+    /// Sets the heading.
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Sets the template, platform, command and source shown with the table.
+    pub fn with_metadata(mut self, metadata: RecordMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Sets the unmatched input lines.
+    pub fn with_unmatched(mut self, unmatched: Vec<(usize, String)>) -> Self {
+        self.unmatched = unmatched;
+        self
+    }
+
+    fn heading(&self) -> &str {
+        self.title
+            .as_deref()
+            .or(self.metadata.command.as_deref())
+            .or(self.metadata.template.as_deref())
+            .unwrap_or("Results")
+    }
+}
+
+impl From<ResultTable> for ReportSection {
+    fn from(table: ResultTable) -> Self {
+        Self::new(table.header, table.records)
+    }
+}
+
+/// A complete HTML document with a table per section.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HtmlReport {
+    /// The document title.
+    pub title: String,
+    /// The tables, in order.
+    pub sections: Vec<ReportSection>,
+}
+
+impl HtmlReport {
+    /// Creates an empty report.
+    pub fn new(title: &str) -> Self {
+        HtmlReport {
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a section.
+    pub fn with_section(mut self, section: ReportSection) -> Self {
+        self.sections.push(section);
+        self
+    }
+
+    /// Adds a section.
+    pub fn add_section(&mut self, section: ReportSection) {
+        self.sections.push(section);
+    }
+
+    /// Returns the report as a string.
+    pub fn render(&self) -> Result<String> {
+        let mut out = vec![];
+        self.write_to(&mut out)?;
+        Ok(String::from_utf8_lossy(&out).into_owned())
+    }
+
+    /// Writes the report to `writer`.
+    pub fn write_to(&self, writer: &mut dyn Write) -> Result<()> {
+        let title = escape(&self.title);
+        writeln!(writer, "<!DOCTYPE html>")?;
+        writeln!(
+            writer,
+            "<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">"
+        )?;
+        writeln!(
+            writer,
+            "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">"
+        )?;
+        writeln!(writer, "<title>{}</title>", title)?;
+        writeln!(writer, "<style>\n{}</style>\n</head>\n<body>", STYLE)?;
+        writeln!(writer, "<h1>{}</h1>", title)?;
+
+        // A table of contents once there is something to navigate
+        if self.sections.len() > 1 {
+            writeln!(writer, "<nav>\n<ul>")?;
+            for (i, section) in self.sections.iter().enumerate() {
+                writeln!(
+                    writer,
+                    "<li><a href=\"#section-{}\">{}</a> ({})</li>",
+                    i + 1,
+                    escape(section.heading()),
+                    plural(section.records.len(), "record")
+                )?;
+            }
+            writeln!(writer, "</ul>\n</nav>")?;
+        }
+
+        for (i, section) in self.sections.iter().enumerate() {
+            self.write_section(writer, i + 1, section)?;
+        }
+        writeln!(writer, "<script>\n{}</script>\n</body>\n</html>", SCRIPT)?;
+        Ok(())
+    }
+
+    fn write_section(
+        &self,
+        writer: &mut dyn Write,
+        number: usize,
+        section: &ReportSection,
+    ) -> Result<()> {
+        writeln!(writer, "<section id=\"section-{}\">", number)?;
+        writeln!(writer, "<h2>{}</h2>", escape(section.heading()))?;
+
+        let metadata = [
+            ("Template", &section.metadata.template),
+            ("Platform", &section.metadata.platform),
+            ("Command", &section.metadata.command),
+            ("Source", &section.metadata.source),
+        ];
+        if metadata.iter().any(|(_, value)| value.is_some()) {
+            writeln!(writer, "<dl class=\"meta\">")?;
+            for (name, value) in metadata {
+                if let Some(value) = value {
+                    writeln!(writer, "<dt>{}</dt><dd>{}</dd>", name, escape(value))?;
+                }
+            }
+            writeln!(writer, "</dl>")?;
+        }
+
+        if !section.unmatched.is_empty() {
+            writeln!(
+                writer,
+                "<details class=\"unmatched\">\n<summary>Warning: {} not matched by the template</summary>\n<pre>",
+                plural(section.unmatched.len(), "input line")
+            )?;
+            for (line, text) in &section.unmatched {
+                writeln!(writer, "{:>5}: {}", line, escape(text))?;
+            }
+            writeln!(writer, "</pre>\n</details>")?;
+        }
+
+        if section.records.is_empty() {
+            writeln!(writer, "<p class=\"empty\">No records.</p>\n</section>")?;
+            return Ok(());
+        }
+        writeln!(
+            writer,
+            "<input class=\"filter\" type=\"search\" placeholder=\"Filter {}\">",
+            plural(section.records.len(), "record")
+        )?;
+        write!(writer, "<table>\n<thead>\n<tr>")?;
+        for h in &section.header {
+            write!(writer, "<th>{}</th>", escape(h))?;
+        }
+        writeln!(writer, "</tr>\n</thead>\n<tbody>")?;
+        for rec in &section.records {
+            write!(writer, "<tr>")?;
+            for h in &section.header {
+                write!(writer, "<td>{}</td>", html_cell(rec.get(h)))?;
+            }
+            writeln!(writer, "</tr>")?;
+        }
+        writeln!(writer, "</tbody>\n</table>\n</section>")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+//! The common capture group schema and a template linter built on it.
+//!
+//! [`Schema::common`] describes the normalized Value names of
+//! `docs/common_capture_groups.md`: whether each one holds a single value or a
+//! list, and what its values look like. [`Schema::lint`] checks a template's
+//! Values against it, and a parser with [`TextFSM::with_schema`](crate::TextFSM::with_schema)
+//! rejects records whose captured values do not have the expected type.
+//!
+//! ```ignore
+//! let fsm = TextFSM::from_file("show_interfaces.textfsm")?;
+//! for issue in Schema::common().lint(&fsm.parser) {
+//!     println!("{}", issue); // INTF: Synonym: use INTERFACE instead of INTF
+//! }
+//! ```
+
+use crate::transform::COMMON_CAPTURE_GROUP_SYNONYMS;
+use crate::{DataRecord, Result, TextFSMParser, TextFsmError, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Whether a capture group holds one value or a list of values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cardinality {
+    /// A plain Value.
+    Single,
+    /// A Value with the `List` option.
+    List,
+}
+
+/// The expected form of a capture group's values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    /// Any text.
+    Text,
+    /// A MAC address, with `:`, `-` or `.` separators or none.
+    MacAddress,
+    /// An IPv4 or IPv6 address.
+    IpAddress,
+    /// An IPv4 address.
+    Ipv4Address,
+    /// An IPv6 address.
+    Ipv6Address,
+    /// A contiguous dotted-quad netmask.
+    Netmask,
+    /// A prefix length, 0 to 128.
+    PrefixLength,
+    /// A VLAN number, 1 to 4094.
+    VlanId,
+}
+
+impl ValueType {
+    /// Returns `true` if `value` has this type. Empty values always match.
+    pub fn matches(&self, value: &str) -> bool {
+        let value = value.trim();
+        if value.is_empty() {
+            return true;
+        }
+        match self {
+            ValueType::Text => true,
+            ValueType::MacAddress => {
+                let hex: Vec<char> = value
+                    .chars()
+                    .filter(|c| !matches!(c, ':' | '-' | '.'))
+                    .collect();
+                hex.len() == 12 && hex.iter().all(|c| c.is_ascii_hexdigit())
+            }
+            ValueType::IpAddress => value.parse::<IpAddr>().is_ok(),
+            ValueType::Ipv4Address => value.parse::<Ipv4Addr>().is_ok(),
+            ValueType::Ipv6Address => value.parse::<Ipv6Addr>().is_ok(),
+            ValueType::Netmask => value.parse::<Ipv4Addr>().is_ok_and(|mask| {
+                let bits = u32::from(mask);
+                bits.leading_ones() + bits.trailing_zeros() == 32
+            }),
+            ValueType::PrefixLength => value.parse::<u8>().is_ok_and(|len| len <= 128),
+            ValueType::VlanId => value
+                .parse::<u16>()
+                .is_ok_and(|id| (1..=4094).contains(&id)),
+        }
+    }
+}
+
+/// A normalized capture group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureGroup {
+    /// The Value name.
+    pub name: String,
+    /// Whether the Value is expected to have the `List` option.
+    pub cardinality: Cardinality,
+    /// The expected form of captured values.
+    pub value_type: ValueType,
+}
+
+impl CaptureGroup {
+    /// Creates a capture group.
+    pub fn new(name: &str, cardinality: Cardinality, value_type: ValueType) -> Self {
+        CaptureGroup {
+            name: name.to_string(),
+            cardinality,
+            value_type,
+        }
+    }
+}
+
+/// The kind of problem found by [`Schema::lint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaIssueKind {
+    /// A non-standard name for a common capture group, e.g. `INTF`.
+    Synonym,
+    /// The `List` option does not match the group's expected cardinality.
+    Cardinality,
+}
+
+/// A problem found in a template's Values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaIssue {
+    /// What kind of problem it is.
+    pub kind: SchemaIssueKind,
+    /// The Value the problem is on.
+    pub value: String,
+    /// A human-readable description.
+    pub message: String,
+}
+
+impl fmt::Display for SchemaIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {:?}: {}", self.value, self.kind, self.message)
+    }
+}
+
+/// A set of capture groups and the synonyms that should be replaced by them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Schema {
+    /// Capture groups by name.
+    pub groups: HashMap<String, CaptureGroup>,
+    /// Non-standard names and the group name to use instead.
+    pub synonyms: HashMap<String, String>,
+}
+
+impl Schema {
+    /// Creates an empty schema.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds a capture group.
+    pub fn with_group(mut self, group: CaptureGroup) -> Self {
+        self.groups.insert(group.name.clone(), group);
+        self
+    }
+
+    /// Adds a synonym to be replaced by `name`.
+    pub fn with_synonym(mut self, synonym: &str, name: &str) -> Self {
+        self.synonyms.insert(synonym.to_string(), name.to_string());
+        self
+    }
+
+    /// The capture groups of `docs/common_capture_groups.md`, with the synonyms
+    /// of [`COMMON_CAPTURE_GROUP_SYNONYMS`].
+    pub fn common() -> Self {
+        use Cardinality::{List, Single};
+        use ValueType::*;
+        let groups = [
+            ("BIA", Single, MacAddress),
+            ("BUNDLE_NAME", Single, Text),
+            ("BUNDLE_PROTOCOL", Single, Text),
+            ("BUNDLE_PROTOCOL_STATE", Single, Text),
+            ("BUNDLE_STATUS", Single, Text),
+            ("CAPABILITIES", Single, Text),
+            ("CAPABILITIES_SUPPORTED", Single, Text),
+            ("CHASSIS_ID", Single, Text),
+            ("DESCRIPTION", Single, Text),
+            ("GATEWAY", Single, IpAddress),
+            ("INTERFACE", Single, Text),
+            ("IP_ADDRESS", Single, IpAddress),
+            ("IP_ADDRESSES", List, IpAddress),
+            ("IP_HELPER", List, IpAddress),
+            ("IP_VERSION", Single, Text),
+            ("IPV6_ADDRESS", Single, Ipv6Address),
+            ("IPV6_ADDRESSES", List, Ipv6Address),
+            ("IPV6_GATEWAY", Single, Ipv6Address),
+            ("LOCAL_INTERFACE", Single, Text),
+            ("LOCAL_IP_ADDRESS", Single, IpAddress),
+            ("MAC_ADDRESS", Single, MacAddress),
+            ("MEMBER_INTERFACE", List, Text),
+            ("MEMBER_INTERFACE_STATUS", List, Text),
+            ("MGMT_ADDRESS", Single, Text),
+            ("MGMT_IP_ADDRESS", Single, IpAddress),
+            ("NEIGHBOR_DESCRIPTION", Single, Text),
+            ("NEIGHBOR_ID", Single, Text),
+            ("NEIGHBOR_INTERFACE", Single, Text),
+            ("NEIGHBOR_INTERFACE_DESCRIPTION", Single, Text),
+            ("NEIGHBOR_NAME", Single, Text),
+            ("NETMASK", Single, Netmask),
+            ("NETWORK", Single, IpAddress),
+            ("PID", Single, Text),
+            ("PLATFORM", Single, Text),
+            ("PREFIX_LENGTH", Single, PrefixLength),
+            ("PROTOCOL", Single, Text),
+            ("ROUTER_ID", Single, Ipv4Address),
+            ("SERIAL", Single, Text),
+            ("VLAN_ID", Single, VlanId),
+            ("VLAN_NAME", Single, Text),
+        ];
+        let schema =
+            groups
+                .into_iter()
+                .fold(Schema::new(), |s, (name, cardinality, value_type)| {
+                    s.with_group(CaptureGroup::new(name, cardinality, value_type))
+                });
+        COMMON_CAPTURE_GROUP_SYNONYMS
+            .iter()
+            .fold(schema, |s, (synonym, name)| s.with_synonym(synonym, name))
+    }
+
+    /// Checks the Values of a template, in declaration order.
+    pub fn lint(&self, parser: &TextFSMParser) -> Vec<SchemaIssue> {
+        let mut issues = vec![];
+        for def in parser.value_definitions() {
+            if let Some(name) = self.synonyms.get(&def.name) {
+                issues.push(SchemaIssue {
+                    kind: SchemaIssueKind::Synonym,
+                    value: def.name.clone(),
+                    message: format!("use {} instead of {}", name, def.name),
+                });
+            }
+            let group = self.groups.get(&def.name).or_else(|| {
+                self.synonyms
+                    .get(&def.name)
+                    .and_then(|n| self.groups.get(n))
+            });
+            if let Some(group) = group {
+                let expected = group.cardinality == Cardinality::List;
+                if def.is_list != expected {
+                    issues.push(SchemaIssue {
+                        kind: SchemaIssueKind::Cardinality,
+                        value: def.name.clone(),
+                        message: if expected {
+                            format!("{} holds a list of values; add the List option", group.name)
+                        } else {
+                            format!(
+                                "{} holds a single value; remove the List option",
+                                group.name
+                            )
+                        },
+                    });
+                }
+            }
+        }
+        issues
+    }
+
+    /// Checks the values of a record against the expected types of their groups.
+    pub fn check_record(&self, record: &DataRecord) -> Result<()> {
+        for (name, value) in &record.fields {
+            let Some(group) = self.groups.get(name) else {
+                continue;
+            };
+            let items = match value {
+                Value::Single(s) => std::slice::from_ref(s),
+                Value::List(l) => l.as_slice(),
+            };
+            if let Some(bad) = items.iter().find(|v| !group.value_type.matches(v)) {
+                return Err(TextFsmError::ParseError(format!(
+                    "value {} '{}' is not a valid {:?}",
+                    name, bad, group.value_type
+                )));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+//! Change detection between two parse runs of the same template.
+//!
+//! A [`ChangeDetector`] compares an older and a newer snapshot of parsed
+//! records and emits [`ChangeEvent`]s: a record appeared, disappeared, or one
+//! of its fields changed. Records are identified by the template's `Key`
+//! Values, so polling a device periodically yields a stream of changes that
+//! serializes to JSON lines.
+//!
+//! ```ignore
+//! let detector = ChangeDetector::from_parser(&fsm.parser);
+//! let events = detector.detect(&previous, &current);
+//! print!("{}", to_json_lines(&events)?);
+//! ```
+
+use crate::diff::{DiffOptions, RecordChange, diff_records};
+use crate::{DataRecord, RecordKey, TextFSMParser, Value};
+use serde::Serialize;
+
+/// A change between two snapshots.
+///
+/// `key` holds the record's `Key` Values; it is empty when the template has none
+/// and records were aligned by best match.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ChangeEvent {
+    /// A record only present in the newer snapshot.
+    Appeared { key: RecordKey, record: DataRecord },
+    /// A record only present in the older snapshot.
+    Disappeared { key: RecordKey, record: DataRecord },
+    /// A field of a record present in both snapshots changed; `None` means absent.
+    FieldChanged {
+        key: RecordKey,
+        field: String,
+        old: Option<Value>,
+        new: Option<Value>,
+    },
+}
+
+/// Compares snapshots of records produced by the same template.
+#[derive(Debug, Clone, Default)]
+pub struct ChangeDetector {
+    /// Fields identifying a record, in declaration order.
+    pub key_fields: Vec<String>,
+}
+
+impl ChangeDetector {
+    /// Creates a detector identifying records by the given fields.
+    pub fn new(key_fields: Vec<String>) -> Self {
+        ChangeDetector { key_fields }
+    }
+
+    /// Creates a detector identifying records by the template's `Key` Values.
+    pub fn from_parser(parser: &TextFSMParser) -> Self {
+        Self::new(parser.key_names())
+    }
+
+    /// Same as [`from_parser`](Self::from_parser), for records parsed with
+    /// `DataRecordConversion::LowercaseKeys`.
+    pub fn from_parser_lowercase(parser: &TextFSMParser) -> Self {
+        Self::new(
+            parser
+                .key_names()
+                .iter()
+                .map(|n| n.to_lowercase())
+                .collect(),
+        )
+    }
+
+    fn key_of(&self, record: &DataRecord) -> RecordKey {
+        RecordKey(
+            self.key_fields
+                .iter()
+                .filter_map(|k| record.get(k).map(|v| (k.clone(), v.clone())))
+                .collect(),
+        )
+    }
+
+    /// Returns the changes from `old` to `new`: field changes first, in the
+    /// order of the old records, then disappeared and appeared records.
+    pub fn detect(&self, old: &[DataRecord], new: &[DataRecord]) -> Vec<ChangeEvent> {
+        let options = DiffOptions {
+            key_fields: self.key_fields.clone(),
+            report_moves: false,
+        };
+        let mut events: Vec<ChangeEvent> = vec![];
+        for change in diff_records(old, new, &options).changes {
+            match change {
+                RecordChange::Added { record, .. } => events.push(ChangeEvent::Appeared {
+                    key: self.key_of(&record),
+                    record,
+                }),
+                RecordChange::Removed { record, .. } => events.push(ChangeEvent::Disappeared {
+                    key: self.key_of(&record),
+                    record,
+                }),
+                RecordChange::Changed {
+                    old_index, fields, ..
+                } => {
+                    let key = self.key_of(&old[old_index]);
+                    events.extend(fields.into_iter().map(|f| ChangeEvent::FieldChanged {
+                        key: key.clone(),
+                        field: f.field,
+                        old: f.old,
+                        new: f.new,
+                    }));
+                }
+                RecordChange::Moved { .. } => {}
+            }
+        }
+        events
+    }
+}
+
+/// Serializes events as JSON lines: one compact JSON object per line.
+#[cfg(feature = "json")]
+pub fn to_json_lines(events: &[ChangeEvent]) -> crate::Result<String> {
+    let mut out = String::new();
+    for event in events {
+        out.push_str(
+            &serde_json::to_string(event)
+                .map_err(|e| crate::TextFsmError::InternalError(e.to_string()))?,
+        );
+        out.push('\n');
+    }
+    Ok(out)
+}
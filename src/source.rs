@@ -0,0 +1,285 @@
+//! Where templates and index files are read from.
+//!
+//! `CliTable` and `TextFSMParser` read through the [`TemplateSource`] trait,
+//! so a template library can come from a directory ([`FileSource`]), from
+//! memory ([`MemorySource`], which can also be filled from tar or zip
+//! archives), or from the binary itself
+//! ([`EmbeddedTemplates`](crate::embed::EmbeddedTemplates)).
+
+use crate::embed::INDEX_NAME;
+use crate::{Result, TextFsmError};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// A read-only collection of TextFSM templates and, optionally, a CLI table index.
+///
+/// Names are paths relative to the root of the library with `/` as separator,
+/// exactly as they appear in the `Template` column of an index.
+pub trait TemplateSource: fmt::Debug + Send + Sync {
+    /// A short description of where the templates live, e.g. a directory.
+    ///
+    /// It is used in error messages and as the namespace of compiled templates
+    /// in the `CliTable` cache, so distinct sources should report distinct locations.
+    fn location(&self) -> String;
+
+    /// Name of the index file inside the library.
+    fn index_name(&self) -> String {
+        INDEX_NAME.to_string()
+    }
+
+    /// Reads the index file of the library.
+    fn read_index(&self) -> Result<String> {
+        self.read_template(&self.index_name())
+    }
+
+    /// Reads a template by name.
+    fn read_template(&self, name: &str) -> Result<String>;
+
+    /// Lists the names of all files in the library, sorted.
+    fn list(&self) -> Result<Vec<String>>;
+}
+
+fn not_found(name: &str, location: &str) -> TextFsmError {
+    TextFsmError::IoError(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        format!("{} not found in {}", name, location),
+    ))
+}
+
+/// Templates stored in a directory on disk, next to their index file.
+#[derive(Debug, Clone)]
+pub struct FileSource {
+    /// Directory holding the templates.
+    pub dir: PathBuf,
+    /// File name of the index inside `dir`.
+    pub index_name: String,
+}
+
+impl FileSource {
+    /// Creates a source for the directory containing the given index file.
+    pub fn from_index<P: AsRef<Path>>(index_path: P) -> Self {
+        let path = index_path.as_ref();
+        FileSource {
+            dir: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+            index_name: path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| INDEX_NAME.to_string()),
+        }
+    }
+
+    /// Creates a source for a template directory whose index is named `index`.
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Self {
+        FileSource {
+            dir: dir.as_ref().to_path_buf(),
+            index_name: INDEX_NAME.to_string(),
+        }
+    }
+}
+
+impl TemplateSource for FileSource {
+    fn location(&self) -> String {
+        self.dir.to_string_lossy().into_owned()
+    }
+
+    fn index_name(&self) -> String {
+        self.index_name.clone()
+    }
+
+    fn read_template(&self, name: &str) -> Result<String> {
+        Ok(std::fs::read_to_string(self.dir.join(name))?)
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        let mut files: Vec<(String, PathBuf)> = vec![];
+        collect_files(&self.dir, "", &mut files)?;
+        let mut names: Vec<String> = files.into_iter().map(|(name, _)| name).collect();
+        names.sort();
+        Ok(names)
+    }
+}
+
+/// Recursively collects the regular files below `dir`, skipping hidden entries.
+///
+/// Names are relative to the starting directory and joined with `/`.
+pub(crate) fn collect_files(
+    dir: &Path,
+    prefix: &str,
+    out: &mut Vec<(String, PathBuf)>,
+) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(fname) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if fname.starts_with('.') {
+            continue;
+        }
+        let rel_name = format!("{}{}", prefix, fname);
+        if path.is_dir() {
+            collect_files(&path, &format!("{}/", rel_name), out)?;
+        } else if path.is_file() {
+            out.push((rel_name, path));
+        }
+    }
+    Ok(())
+}
+
+/// Templates held in memory, keyed by name.
+///
+/// Useful for tests and for template bundles shipped as a single tar or zip
+/// archive, which are unpacked into memory when loaded.
+#[derive(Debug, Clone, Default)]
+pub struct MemorySource {
+    /// Name of the source, reported as `memory:<name>` by [`TemplateSource::location`].
+    pub name: String,
+    /// File contents keyed by relative path.
+    pub files: BTreeMap<String, String>,
+}
+
+impl MemorySource {
+    /// Creates an empty in-memory source.
+    pub fn new(name: &str) -> Self {
+        MemorySource {
+            name: name.to_string(),
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Adds or replaces a file.
+    pub fn insert(&mut self, fname: &str, content: &str) -> &mut Self {
+        self.files.insert(fname.to_string(), content.to_string());
+        self
+    }
+
+    /// Keeps only the part of an unpacked archive below the directory holding its index.
+    ///
+    /// Archives usually wrap the library in one or more directories
+    /// (`ntc-templates-x.y/ntc_templates/templates/index`); the shallowest index
+    /// found decides the root. Archives without an index are kept as they are.
+    #[cfg(any(feature = "tar", feature = "zip"))]
+    fn strip_to_index_root(&mut self) {
+        let root = self
+            .files
+            .keys()
+            .filter(|k| *k == INDEX_NAME || k.ends_with(&format!("/{}", INDEX_NAME)))
+            .min_by_key(|k| k.matches('/').count())
+            .map(|k| k[..k.len() - INDEX_NAME.len()].to_string());
+        let Some(root) = root else {
+            return;
+        };
+        if root.is_empty() {
+            return;
+        }
+        log::trace!("Archive root: {}", &root);
+        self.files = std::mem::take(&mut self.files)
+            .into_iter()
+            .filter_map(|(k, v)| k.strip_prefix(&root).map(|k| (k.to_string(), v)))
+            .collect();
+    }
+
+    /// Adds an archive member, skipping directories and files that are not UTF-8 text.
+    #[cfg(any(feature = "tar", feature = "zip"))]
+    fn insert_archive_member(&mut self, path: &str, data: Vec<u8>) {
+        let path = path.trim_start_matches("./");
+        if path.is_empty() || path.ends_with('/') {
+            return;
+        }
+        match String::from_utf8(data) {
+            Ok(content) => {
+                self.files.insert(path.to_string(), content);
+            }
+            Err(_) => log::debug!("Skipping non UTF-8 archive member {}", path),
+        }
+    }
+
+    /// Loads a `.tar` or `.tar.gz` archive from a reader.
+    #[cfg(feature = "tar")]
+    pub fn from_tar<R: std::io::Read>(name: &str, mut reader: R) -> Result<Self> {
+        use std::io::Read;
+        let mut data: Vec<u8> = vec![];
+        reader.read_to_end(&mut data)?;
+        // gzip magic number
+        let input: Box<dyn Read> = if data.starts_with(&[0x1f, 0x8b]) {
+            Box::new(flate2::read::GzDecoder::new(data.as_slice()))
+        } else {
+            Box::new(data.as_slice())
+        };
+
+        let mut source = MemorySource::new(name);
+        let mut archive = tar::Archive::new(input);
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let path = entry.path()?.to_string_lossy().into_owned();
+            let mut content: Vec<u8> = vec![];
+            entry.read_to_end(&mut content)?;
+            source.insert_archive_member(&path, content);
+        }
+        source.strip_to_index_root();
+        log::debug!("Loaded {} files from tar '{}'", source.files.len(), name);
+        Ok(source)
+    }
+
+    /// Loads a `.tar` or `.tar.gz` archive from a file.
+    #[cfg(feature = "tar")]
+    pub fn from_tar_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)?;
+        Self::from_tar(&path.to_string_lossy(), file)
+    }
+
+    /// Loads a zip archive from a reader.
+    #[cfg(feature = "zip")]
+    pub fn from_zip<R: std::io::Read + std::io::Seek>(name: &str, reader: R) -> Result<Self> {
+        use std::io::Read;
+        let zip_err = |e: zip::result::ZipError| TextFsmError::IoError(std::io::Error::other(e));
+        let mut archive = zip::ZipArchive::new(reader).map_err(zip_err)?;
+
+        let mut source = MemorySource::new(name);
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i).map_err(zip_err)?;
+            if !entry.is_file() {
+                continue;
+            }
+            let Some(path) = entry.enclosed_name() else {
+                continue;
+            };
+            let path = path.to_string_lossy().replace('\\', "/");
+            let mut content: Vec<u8> = vec![];
+            entry.read_to_end(&mut content)?;
+            source.insert_archive_member(&path, content);
+        }
+        source.strip_to_index_root();
+        log::debug!("Loaded {} files from zip '{}'", source.files.len(), name);
+        Ok(source)
+    }
+
+    /// Loads a zip archive from a file.
+    #[cfg(feature = "zip")]
+    pub fn from_zip_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)?;
+        Self::from_zip(&path.to_string_lossy(), std::io::BufReader::new(file))
+    }
+}
+
+impl TemplateSource for MemorySource {
+    fn location(&self) -> String {
+        format!("memory:{}", self.name)
+    }
+
+    fn read_template(&self, name: &str) -> Result<String> {
+        self.files
+            .get(name)
+            .cloned()
+            .ok_or_else(|| not_found(name, &self.location()))
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        Ok(self.files.keys().cloned().collect())
+    }
+}
@@ -0,0 +1,253 @@
+//! Storing parse results in SQLite.
+//!
+//! A [`SqliteWriter`] writes records into one table, creating it on first use
+//! with a `TEXT` column per Value and `List` values stored as JSON arrays. When
+//! the template has `Key` Values they form the table's primary key and rows are
+//! upserted, so parsing the same command again updates the stored state instead
+//! of duplicating it. Key columns are `NOT NULL`, with missing key values
+//! stored as `''`; an existing table without the primary key gets a unique
+//! index on the key columns instead. Tables without keys are appended to.
+//!
+//! ```ignore
+//! let conn = rusqlite::Connection::open("state.db")?;
+//! let fsm = TextFSM::from_file("cisco_ios_show_interfaces.textfsm")?;
+//! let mut writer = SqliteWriter::new(&conn, "cisco_ios_show_interfaces", &fsm)?;
+//! for record in fsm.parse_reader(input) {
+//!     writer.write_record(&record?)?;
+//! }
+//! ```
+
+use crate::{DataRecord, Result, ResultTable, TextFSM, TextFsmError, Value};
+use rusqlite::Connection;
+use rusqlite::types::ToSqlOutput;
+use std::collections::BTreeSet;
+
+/// Returns a table name for a template file or command, e.g.
+/// `cisco_ios_show_ip_route` for `cisco_ios_show_ip_route.textfsm` or
+/// `cisco_ios show ip route`.
+pub fn table_name(name: &str) -> String {
+    let name = name.strip_suffix(".textfsm").unwrap_or(name);
+    let name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    let mut table = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            table.push(c.to_ascii_lowercase());
+        } else if !table.is_empty() && !table.ends_with('_') {
+            table.push('_');
+        }
+    }
+    let table = table.trim_end_matches('_');
+    if table.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("t_{}", table)
+    } else {
+        table.to_string()
+    }
+}
+
+fn quote(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+fn sql_value(value: Option<&Value>) -> Result<ToSqlOutput<'_>> {
+    Ok(match value {
+        Some(Value::Single(s)) => ToSqlOutput::from(s.as_str()),
+        Some(Value::List(l)) => ToSqlOutput::from(
+            serde_json::to_string(l).map_err(|e| TextFsmError::InternalError(e.to_string()))?,
+        ),
+        None => ToSqlOutput::from(rusqlite::types::Null),
+    })
+}
+
+/// Writes records into a SQLite table, upserting them by key.
+pub struct SqliteWriter<'c> {
+    conn: &'c Connection,
+    columns: Vec<String>,
+    key: Vec<String>,
+    sql: String,
+    count: usize,
+}
+
+impl<'c> SqliteWriter<'c> {
+    /// Creates a writer for records of `fsm`: a column per Value, named as the
+    /// parser's pipeline names them, keyed by the template's `Key` Values.
+    pub fn new(conn: &'c Connection, table: &str, fsm: &TextFSM) -> Result<Self> {
+        let pipeline = fsm.output_pipeline(None);
+        let columns = fsm.header(None);
+        let key = pipeline.header(&fsm.parser.key_names());
+        Self::with_columns(conn, table, columns, key)
+    }
+
+    /// Creates a writer with the given columns and key columns.
+    ///
+    /// The table is created if it does not exist; columns it lacks are added.
+    pub fn with_columns(
+        conn: &'c Connection,
+        table: &str,
+        columns: Vec<String>,
+        key: Vec<String>,
+    ) -> Result<Self> {
+        // Key columns are NOT NULL: SQLite lets a TEXT primary key hold NULLs,
+        // which never conflict, so a missing key value is stored as ''
+        let column_def = |c: &String| {
+            if key.contains(c) {
+                format!("{} TEXT NOT NULL DEFAULT ''", quote(c))
+            } else {
+                format!("{} TEXT", quote(c))
+            }
+        };
+        let existing = conn
+            .prepare(&format!("PRAGMA table_info({})", quote(table)))?
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<rusqlite::Result<BTreeSet<String>>>()?;
+        let keys: Vec<String> = key.iter().map(|k| quote(k)).collect();
+        if existing.is_empty() {
+            let defs: Vec<String> = columns.iter().map(column_def).collect();
+            let primary_key = if key.is_empty() {
+                String::new()
+            } else {
+                format!(", PRIMARY KEY ({})", keys.join(", "))
+            };
+            conn.execute(
+                &format!(
+                    "CREATE TABLE IF NOT EXISTS {} ({}{})",
+                    quote(table),
+                    defs.join(", "),
+                    primary_key
+                ),
+                [],
+            )?;
+        } else {
+            for column in columns.iter().filter(|c| !existing.contains(*c)) {
+                conn.execute(
+                    &format!(
+                        "ALTER TABLE {} ADD COLUMN {}",
+                        quote(table),
+                        column_def(column)
+                    ),
+                    [],
+                )?;
+            }
+            // A table created without the key, e.g. by an older template,
+            // needs a unique index for the upsert to find conflicts
+            if !key.is_empty() {
+                conn.execute(
+                    &format!(
+                        "CREATE UNIQUE INDEX IF NOT EXISTS {} ON {} ({})",
+                        quote(&format!("{}_key", table)),
+                        quote(table),
+                        keys.join(", ")
+                    ),
+                    [],
+                )?;
+            }
+        }
+
+        let names: Vec<String> = columns.iter().map(|c| quote(c)).collect();
+        let params: Vec<String> = (1..=columns.len()).map(|i| format!("?{}", i)).collect();
+        let mut sql = format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            quote(table),
+            names.join(", "),
+            params.join(", ")
+        );
+        if !key.is_empty() {
+            let updates: Vec<String> = columns
+                .iter()
+                .filter(|c| !key.contains(c))
+                .map(|c| format!("{0} = excluded.{0}", quote(c)))
+                .collect();
+            if updates.is_empty() {
+                sql.push_str(&format!(" ON CONFLICT ({}) DO NOTHING", keys.join(", ")));
+            } else {
+                sql.push_str(&format!(
+                    " ON CONFLICT ({}) DO UPDATE SET {}",
+                    keys.join(", "),
+                    updates.join(", ")
+                ));
+            }
+        }
+        Ok(SqliteWriter {
+            conn,
+            columns,
+            key,
+            sql,
+            count: 0,
+        })
+    }
+
+    /// Number of records written so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Inserts a record, or updates the row with the same key. Fields outside
+    /// the table's columns are ignored.
+    pub fn write_record(&mut self, rec: &DataRecord) -> Result<()> {
+        let values = self
+            .columns
+            .iter()
+            .map(|c| match rec.fields.get(c) {
+                None if self.key.contains(c) => Ok(ToSqlOutput::from("")),
+                value => sql_value(value),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut stmt = self.conn.prepare_cached(&self.sql)?;
+        stmt.execute(rusqlite::params_from_iter(values))?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Writes all records in a single transaction.
+    pub fn write_all<'r, I>(&mut self, records: I) -> Result<()>
+    where
+        I: IntoIterator<Item = &'r DataRecord>,
+    {
+        let tx = self.conn.unchecked_transaction()?;
+        for rec in records {
+            self.write_record(rec)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+/// Trait to store parsing results in a SQLite table.
+pub trait SqliteExport {
+    /// Writes the results into `table`, creating it if needed, and returns the
+    /// number of records written.
+    fn export_sqlite(&self, conn: &Connection, table: &str) -> Result<usize>;
+}
+
+/// Columns are the sorted field names; the key is the first record's key.
+impl SqliteExport for Vec<DataRecord> {
+    fn export_sqlite(&self, conn: &Connection, table: &str) -> Result<usize> {
+        if self.is_empty() {
+            return Ok(0);
+        }
+        let columns: BTreeSet<&String> = self.iter().flat_map(|r| r.fields.keys()).collect();
+        let key = self
+            .first()
+            .and_then(|r| r.record_key.as_ref())
+            .map(|k| k.0.iter().map(|(name, _)| name.clone()).collect())
+            .unwrap_or_default();
+        let mut writer =
+            SqliteWriter::with_columns(conn, table, columns.into_iter().cloned().collect(), key)?;
+        writer.write_all(self)?;
+        Ok(writer.count())
+    }
+}
+
+/// Columns follow the table's header; the key is the first record's key.
+impl SqliteExport for ResultTable {
+    fn export_sqlite(&self, conn: &Connection, table: &str) -> Result<usize> {
+        let key = self
+            .records
+            .first()
+            .and_then(|r| r.record_key.as_ref())
+            .map(|k| k.0.iter().map(|(name, _)| name.clone()).collect())
+            .unwrap_or_default();
+        let mut writer = SqliteWriter::with_columns(conn, table, self.header.clone(), key)?;
+        writer.write_all(&self.records)?;
+        Ok(writer.count())
+    }
+}
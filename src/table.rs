@@ -0,0 +1,272 @@
+//! A table of parse results with lookup, filtering and grouping helpers.
+//!
+//! [`ResultTable`] wraps the records of a parse together with the template's
+//! header (its Value names in declaration order). It is returned by
+//! [`TextFSM::parse_string_table`](crate::TextFSM::parse_string_table) and
+//! [`TextFSM::parse_file_table`](crate::TextFSM::parse_file_table), or built
+//! from any `Vec<DataRecord>`.
+//!
+//! ```ignore
+//! let interfaces = fsm_interfaces.parse_file_table("show_interfaces.txt", None)?;
+//! let brief = fsm_brief.parse_file_table("show_ip_interface_brief.txt", None)?;
+//! let joined = interfaces.join(&brief, "INTERFACE");
+//! let by_status = joined.group_by("LINK_STATUS");
+//! ```
+
+use crate::export::{ExportOptions, OutputFormat, RecordSink, TextFsmExport};
+use crate::{DataRecord, RecordKey, TextFsmError, Value};
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// Parse results together with their header.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ResultTable {
+    /// Field names in column order.
+    pub header: Vec<String>,
+    /// The records, one per row.
+    pub records: Vec<DataRecord>,
+}
+
+/// Compares two values: numbers numerically and before all other text, which
+/// is compared as strings. `NaN` and `inf` count as text, so the order is total.
+pub fn compare_values(a: &Value, b: &Value) -> Ordering {
+    fn number(s: &str) -> Option<f64> {
+        s.parse::<f64>().ok().filter(|n| n.is_finite())
+    }
+    match (a, b) {
+        (Value::Single(x), Value::Single(y)) => match (number(x), number(y)) {
+            (Some(nx), Some(ny)) => nx.total_cmp(&ny).then_with(|| x.cmp(y)),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => x.cmp(y),
+        },
+        _ => a.cmp(b),
+    }
+}
+
+impl ResultTable {
+    /// Creates a table from a header and records.
+    pub fn new(header: Vec<String>, records: Vec<DataRecord>) -> Self {
+        ResultTable { header, records }
+    }
+
+    /// Creates a table from records, taking the header from their field names, sorted.
+    pub fn from_records(records: Vec<DataRecord>) -> Self {
+        let mut header: Vec<String> = records
+            .iter()
+            .flat_map(|r| r.fields.keys().cloned())
+            .collect();
+        header.sort();
+        header.dedup();
+        ResultTable { header, records }
+    }
+
+    /// Number of rows.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns `true` if the table has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Returns an iterator over the rows.
+    pub fn iter(&self) -> std::slice::Iter<'_, DataRecord> {
+        self.records.iter()
+    }
+
+    /// Returns the records.
+    pub fn into_records(self) -> Vec<DataRecord> {
+        self.records
+    }
+
+    fn with_records(&self, records: Vec<DataRecord>) -> Self {
+        ResultTable {
+            header: self.header.clone(),
+            records,
+        }
+    }
+
+    /// Returns the first row with the given `record_key`.
+    pub fn get_by_key(&self, key: &RecordKey) -> Option<&DataRecord> {
+        self.records
+            .iter()
+            .find(|r| r.record_key.as_ref() == Some(key))
+    }
+
+    /// Returns the first row whose key values are `values`, in key declaration order.
+    pub fn get_by_key_values(&self, values: &[&str]) -> Option<&DataRecord> {
+        self.records.iter().find(|r| {
+            r.record_key.as_ref().is_some_and(|k| {
+                k.len() == values.len()
+                    && k.iter()
+                        .zip(values)
+                        .all(|((_, v), want)| matches!(v, Value::Single(s) if s == want))
+            })
+        })
+    }
+
+    /// Returns the rows where `field` exists and satisfies `predicate`.
+    pub fn filter<F: Fn(&Value) -> bool>(&self, field: &str, predicate: F) -> Self {
+        self.with_records(
+            self.records
+                .iter()
+                .filter(|r| r.get(field).is_some_and(&predicate))
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// Groups rows by the value of `field`. Rows without the field are left out.
+    pub fn group_by(&self, field: &str) -> BTreeMap<Value, ResultTable> {
+        let mut groups: BTreeMap<Value, ResultTable> = BTreeMap::new();
+        for rec in &self.records {
+            if let Some(value) = rec.get(field) {
+                groups
+                    .entry(value.clone())
+                    .or_insert_with(|| self.with_records(vec![]))
+                    .records
+                    .push(rec.clone());
+            }
+        }
+        groups
+    }
+
+    /// Returns the values of one column. Rows without the field are skipped.
+    pub fn column(&self, name: &str) -> Vec<&Value> {
+        self.records.iter().filter_map(|r| r.get(name)).collect()
+    }
+
+    /// Returns a table with only the given fields, in the given order.
+    pub fn select(&self, fields: &[&str]) -> Self {
+        let records = self
+            .records
+            .iter()
+            .map(|r| DataRecord {
+                fields: r
+                    .fields
+                    .iter()
+                    .filter(|(k, _)| fields.contains(&k.as_str()))
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect(),
+                record_key: r.record_key.clone(),
+            })
+            .collect();
+        ResultTable {
+            header: fields.iter().map(|f| f.to_string()).collect(),
+            records,
+        }
+    }
+
+    /// Sorts the rows by the given fields, in order of priority. The sort is stable
+    /// and rows missing a field sort first.
+    pub fn sort_by(&mut self, fields: &[&str]) {
+        self.records.sort_by(|a, b| {
+            fields
+                .iter()
+                .map(|f| match (a.get(f), b.get(f)) {
+                    (Some(x), Some(y)) => compare_values(x, y),
+                    (x, y) => x.is_some().cmp(&y.is_some()),
+                })
+                .find(|o| *o != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        });
+    }
+
+    fn join_impl(&self, other: &ResultTable, field: &str, keep_unmatched: bool) -> Self {
+        let mut header = self.header.clone();
+        header.extend(
+            other
+                .header
+                .iter()
+                .filter(|h| !self.header.contains(h))
+                .cloned(),
+        );
+
+        let mut records: Vec<DataRecord> = vec![];
+        for left in &self.records {
+            let matches: Vec<&DataRecord> = match left.get(field) {
+                Some(value) => other
+                    .records
+                    .iter()
+                    .filter(|r| r.get(field) == Some(value))
+                    .collect(),
+                None => vec![],
+            };
+            if matches.is_empty() && keep_unmatched {
+                records.push(left.clone());
+            }
+            for right in matches {
+                let mut rec = left.clone();
+                for (k, v) in &right.fields {
+                    rec.fields.entry(k.clone()).or_insert_with(|| v.clone());
+                }
+                records.push(rec);
+            }
+        }
+        ResultTable { header, records }
+    }
+
+    /// Inner join with `other` on equal values of `field`.
+    ///
+    /// Each row is merged with every matching row of `other`; fields present on
+    /// both sides keep this table's value. Rows keep this table's `record_key`.
+    pub fn join(&self, other: &ResultTable, field: &str) -> Self {
+        self.join_impl(other, field, false)
+    }
+
+    /// Same as [`join`](Self::join), but rows without a match in `other` are kept as they are.
+    pub fn left_join(&self, other: &ResultTable, field: &str) -> Self {
+        self.join_impl(other, field, true)
+    }
+}
+
+impl From<Vec<DataRecord>> for ResultTable {
+    fn from(records: Vec<DataRecord>) -> Self {
+        Self::from_records(records)
+    }
+}
+
+impl From<ResultTable> for Vec<DataRecord> {
+    fn from(table: ResultTable) -> Self {
+        table.records
+    }
+}
+
+impl IntoIterator for ResultTable {
+    type Item = DataRecord;
+    type IntoIter = std::vec::IntoIter<DataRecord>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.records.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a ResultTable {
+    type Item = &'a DataRecord;
+    type IntoIter = std::slice::Iter<'a, DataRecord>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.records.iter()
+    }
+}
+
+impl TextFsmExport for ResultTable {
+    fn export_to_with(
+        &self,
+        format: OutputFormat,
+        options: &ExportOptions,
+        writer: &mut dyn std::io::Write,
+    ) -> Result<(), TextFsmError> {
+        let mut sink = RecordSink::new(writer, format)
+            .with_header(self.header.clone())
+            .with_options(options.clone());
+        for rec in &self.records {
+            sink.write_record(rec)?;
+        }
+        sink.finish()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,336 @@
+//! Rendering of records as tables for the terminal.
+//!
+//! The text export lays records out with a [`TableStyle`]: columns are sized
+//! by Unicode display width, cells longer than `max_column_width` are wrapped
+//! or truncated, and wide records can be shown one block per record instead of
+//! one row per record.
+//!
+//! ```text
+//! ┌───────────┬────────┐          -[ RECORD 1 ]------------
+//! │ INTERFACE │ STATUS │          INTERFACE | Gi0/1
+//! ├───────────┼────────┤          STATUS    | up
+//! │ Gi0/1     │ up     │          -[ RECORD 2 ]------------
+//! │ Gi0/2     │ down   │          INTERFACE | Gi0/2
+//! └───────────┴────────┘          STATUS    | down
+//! ```
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+const HEADER_COLOR: &str = "\x1b[1;36m";
+const RESET: &str = "\x1b[0m";
+
+/// The lines drawn around and between cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BorderStyle {
+    /// Columns separated by two spaces, with a dashed line under the header.
+    #[default]
+    Plain,
+    /// `+`, `-` and `|` borders.
+    Ascii,
+    /// Unicode box-drawing borders.
+    Box,
+}
+
+/// What to do with cells wider than the maximum column width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+    /// Wrap the text over several lines, at spaces where possible.
+    #[default]
+    Wrap,
+    /// Cut the text and end it with `…`.
+    Truncate,
+}
+
+/// How records are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Layout {
+    /// One row per record.
+    #[default]
+    Horizontal,
+    /// One block of `field | value` lines per record.
+    Vertical,
+    /// Horizontal, unless the table is wider than `max_width`.
+    Auto,
+}
+
+/// How the text table is drawn.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TableStyle {
+    /// The borders.
+    pub border: BorderStyle,
+    /// The layout.
+    pub layout: Layout,
+    /// The maximum display width of a column's content.
+    pub max_column_width: Option<usize>,
+    /// What to do with wider cells.
+    pub overflow: Overflow,
+    /// The width available, e.g. of the terminal, for [`Layout::Auto`].
+    pub max_width: Option<usize>,
+    /// Highlight the header (or field names) with ANSI colors.
+    pub color: bool,
+}
+
+impl TableStyle {
+    /// Creates the default style, the plain table.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the borders.
+    pub fn with_border(mut self, border: BorderStyle) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// Sets the layout.
+    pub fn with_layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Limits the display width of columns, wrapping or truncating wider cells.
+    pub fn with_max_column_width(mut self, width: usize, overflow: Overflow) -> Self {
+        self.max_column_width = Some(width.max(1));
+        self.overflow = overflow;
+        self
+    }
+
+    /// Sets the width available for [`Layout::Auto`].
+    pub fn with_max_width(mut self, width: usize) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+
+    /// Turns ANSI colors on or off.
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Fits a cell's lines into `width` columns.
+    fn fit(&self, lines: &[String], width: usize) -> Vec<String> {
+        let lines = lines.iter().flat_map(|l| l.lines());
+        match self.overflow {
+            Overflow::Wrap => lines.flat_map(|l| wrap(l, width)).collect(),
+            Overflow::Truncate => lines.map(|l| truncate(l, width)).collect(),
+        }
+    }
+
+    fn highlight(&self, text: &str) -> String {
+        if self.color && !text.is_empty() {
+            format!("{}{}{}", HEADER_COLOR, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// Returns the number of terminal columns `text` takes.
+pub fn display_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+/// Splits `text` into lines of at most `width` columns, at spaces where possible.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    if display_width(text) <= width {
+        return vec![text.to_string()];
+    }
+    let mut lines = vec![];
+    let mut line = String::new();
+    for word in text.split(' ') {
+        let sep = usize::from(!line.is_empty());
+        if display_width(&line) + sep + display_width(word) <= width {
+            if sep == 1 {
+                line.push(' ');
+            }
+            line.push_str(word);
+            continue;
+        }
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        // Break words that are longer than a line
+        for c in word.chars() {
+            let w = c.width().unwrap_or(0);
+            if display_width(&line) + w > width && !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            line.push(c);
+        }
+    }
+    lines.push(line);
+    lines
+}
+
+/// Cuts `text` to at most `width` columns, ending it with `…` if it was cut.
+fn truncate(text: &str, width: usize) -> String {
+    if display_width(text) <= width {
+        return text.to_string();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w > width.saturating_sub(1) {
+            break;
+        }
+        used += w;
+        out.push(c);
+    }
+    out.push('…');
+    out
+}
+
+/// Pads `text` with spaces to `width` columns.
+fn pad(text: &str, width: usize) -> String {
+    let fill = width.saturating_sub(display_width(text));
+    format!("{}{}", text, " ".repeat(fill))
+}
+
+/// Renders a table. Each row holds the lines of each of its cells.
+pub fn render(headers: &[String], rows: &[Vec<Vec<String>>], style: &TableStyle) -> String {
+    if headers.is_empty() {
+        return String::new();
+    }
+    let limit = style.max_column_width.unwrap_or(usize::MAX);
+    let fit_row = |cells: &[Vec<String>]| -> Vec<Vec<String>> {
+        cells.iter().map(|lines| style.fit(lines, limit)).collect()
+    };
+    let header: Vec<Vec<String>> = headers.iter().map(|h| vec![h.clone()]).collect();
+    let header = fit_row(&header);
+    let rows: Vec<Vec<Vec<String>>> = rows.iter().map(|r| fit_row(r)).collect();
+
+    let mut widths: Vec<usize> = header
+        .iter()
+        .map(|cell| cell.iter().map(|l| display_width(l)).max().unwrap_or(0))
+        .collect();
+    for row in &rows {
+        for (i, cell) in row.iter().enumerate() {
+            for line in cell {
+                widths[i] = widths[i].max(display_width(line));
+            }
+        }
+    }
+
+    let vertical = match style.layout {
+        Layout::Horizontal => false,
+        Layout::Vertical => true,
+        Layout::Auto => {
+            let total: usize = widths.iter().sum();
+            let borders = match style.border {
+                BorderStyle::Plain => 2 * (widths.len() - 1),
+                _ => 3 * widths.len() + 1,
+            };
+            style.max_width.is_some_and(|max| total + borders > max)
+        }
+    };
+    if vertical {
+        render_vertical(headers, &rows, style)
+    } else {
+        render_horizontal(&header, &rows, &widths, style)
+    }
+}
+
+fn render_horizontal(
+    header: &[Vec<String>],
+    rows: &[Vec<Vec<String>>],
+    widths: &[usize],
+    style: &TableStyle,
+) -> String {
+    let mut out = String::new();
+    // (left, fill, middle, right) of the horizontal rules
+    let rule = |out: &mut String, (left, fill, middle, right): (&str, &str, &str, &str)| {
+        let parts: Vec<String> = widths.iter().map(|w| fill.repeat(w + 2)).collect();
+        out.push_str(&format!("{}{}{}\n", left, parts.join(middle), right));
+    };
+    let row_lines = |out: &mut String, cells: &[Vec<String>], is_header: bool| {
+        let height = cells.iter().map(|c| c.len()).max().unwrap_or(0).max(1);
+        for line in 0..height {
+            let texts: Vec<String> = cells
+                .iter()
+                .zip(widths)
+                .map(|(cell, w)| {
+                    let text = cell.get(line).map(|s| s.as_str()).unwrap_or_default();
+                    let padding = " ".repeat(w.saturating_sub(display_width(text)));
+                    if is_header {
+                        format!("{}{}", style.highlight(text), padding)
+                    } else {
+                        format!("{}{}", text, padding)
+                    }
+                })
+                .collect();
+            let line = match style.border {
+                BorderStyle::Plain => texts.join("  ").trim_end().to_string(),
+                BorderStyle::Ascii => format!("| {} |", texts.join(" | ")),
+                BorderStyle::Box => format!("│ {} │", texts.join(" │ ")),
+            };
+            out.push_str(&line);
+            out.push('\n');
+        }
+    };
+
+    match style.border {
+        BorderStyle::Plain => {
+            row_lines(&mut out, header, true);
+            let dashes: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+            out.push_str(dashes.join("  ").trim_end());
+            out.push('\n');
+            for row in rows {
+                row_lines(&mut out, row, false);
+            }
+        }
+        BorderStyle::Ascii => {
+            rule(&mut out, ("+", "-", "+", "+"));
+            row_lines(&mut out, header, true);
+            rule(&mut out, ("+", "-", "+", "+"));
+            for row in rows {
+                row_lines(&mut out, row, false);
+            }
+            rule(&mut out, ("+", "-", "+", "+"));
+        }
+        BorderStyle::Box => {
+            rule(&mut out, ("┌", "─", "┬", "┐"));
+            row_lines(&mut out, header, true);
+            rule(&mut out, ("├", "─", "┼", "┤"));
+            for row in rows {
+                row_lines(&mut out, row, false);
+            }
+            rule(&mut out, ("└", "─", "┴", "┘"));
+        }
+    }
+    out
+}
+
+fn render_vertical(headers: &[String], rows: &[Vec<Vec<String>>], style: &TableStyle) -> String {
+    let key_width = headers.iter().map(|h| display_width(h)).max().unwrap_or(0);
+    let value_width = rows
+        .iter()
+        .flatten()
+        .flatten()
+        .map(|l| display_width(l))
+        .max()
+        .unwrap_or(0);
+    let mut out = String::new();
+    for (n, row) in rows.iter().enumerate() {
+        let title = format!("-[ RECORD {} ]", n + 1);
+        let fill = (key_width + 3 + value_width).saturating_sub(display_width(&title));
+        out.push_str(&format!("{}{}\n", title, "-".repeat(fill)));
+        for (name, cell) in headers.iter().zip(row) {
+            let key = format!(
+                "{}{}",
+                style.highlight(name),
+                pad("", key_width - display_width(name))
+            );
+            let blank = " ".repeat(key_width);
+            if cell.is_empty() {
+                out.push_str(&format!("{} |\n", key));
+            }
+            for (i, line) in cell.iter().enumerate() {
+                let key = if i == 0 { &key } else { &blank };
+                out.push_str(format!("{} | {}", key, line).trim_end());
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
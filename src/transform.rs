@@ -0,0 +1,294 @@
+//! Post-processing of parsed records.
+//!
+//! A [`Pipeline`] is an ordered list of [`Transform`] steps applied to every
+//! record a [`TextFSM`](crate::TextFSM) produces, whichever parse method is
+//! used (`parse_string`, `parse_file`, `parse_reader` and the table variants).
+//!
+//! ```ignore
+//! let pipeline = Pipeline::new()
+//!     .trim()
+//!     .drop_empty()
+//!     .rename_common()
+//!     .split("MEMBER_INTERFACE", r",\s*")?
+//!     .lowercase_keys();
+//! let mut fsm = TextFSM::from_file("show_etherchannel.textfsm")?.with_pipeline(pipeline);
+//! ```
+
+use crate::{DataRecord, DataRecordConversion, Result, TextFsmError, Value};
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Vendor or legacy capture group names and the normalized names from
+/// `docs/common_capture_groups.md` they map to.
+pub const COMMON_CAPTURE_GROUP_SYNONYMS: &[(&str, &str)] = &[
+    ("IFACE", "INTERFACE"),
+    ("INTF", "INTERFACE"),
+    ("INTFC", "INTERFACE"),
+    ("MAC", "MAC_ADDRESS"),
+    ("MACADDR", "MAC_ADDRESS"),
+    ("MGMT_IP", "MGMT_IP_ADDRESS"),
+    ("MANAGEMENT_IP", "MGMT_IP_ADDRESS"),
+    ("PROTO", "PROTOCOL"),
+    ("VLAN", "VLAN_ID"),
+    ("VLANID", "VLAN_ID"),
+    ("TAG", "VLAN_ID"),
+    ("ROUTE", "NETWORK"),
+    ("PREFIX", "PREFIX_LENGTH"),
+    ("CIDR", "PREFIX_LENGTH"),
+];
+
+/// A user-supplied transform, called with each record.
+pub type RecordFn = Arc<dyn Fn(&mut DataRecord) + Send + Sync>;
+
+/// A single post-processing step.
+#[derive(Clone)]
+pub enum Transform {
+    /// Convert all field names to lowercase.
+    LowercaseKeys,
+    /// Convert all field names to uppercase.
+    UppercaseKeys,
+    /// Rename fields; names not in the map are kept.
+    Rename(HashMap<String, String>),
+    /// Remove fields whose value is an empty string or an empty list.
+    DropEmpty,
+    /// Trim whitespace around values and list items.
+    Trim,
+    /// Split a field into a list on a regex; empty parts are dropped.
+    Split { field: String, separator: Regex },
+    /// Call a closure with each record.
+    Map(RecordFn),
+}
+
+impl fmt::Debug for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Transform::LowercaseKeys => write!(f, "LowercaseKeys"),
+            Transform::UppercaseKeys => write!(f, "UppercaseKeys"),
+            Transform::Rename(map) => f.debug_tuple("Rename").field(map).finish(),
+            Transform::DropEmpty => write!(f, "DropEmpty"),
+            Transform::Trim => write!(f, "Trim"),
+            Transform::Split { field, separator } => f
+                .debug_struct("Split")
+                .field("field", field)
+                .field("separator", &separator.as_str())
+                .finish(),
+            Transform::Map(_) => write!(f, "Map(..)"),
+        }
+    }
+}
+
+impl Transform {
+    /// Returns the new name of a field, for steps that rename fields.
+    fn rename_field(&self, name: &str) -> Option<String> {
+        match self {
+            Transform::LowercaseKeys => Some(name.to_lowercase()),
+            Transform::UppercaseKeys => Some(name.to_uppercase()),
+            Transform::Rename(map) => map.get(name).cloned(),
+            _ => None,
+        }
+    }
+
+    fn apply(&self, record: &mut DataRecord) {
+        match self {
+            Transform::LowercaseKeys | Transform::UppercaseKeys | Transform::Rename(_) => {
+                let mut fields = HashMap::with_capacity(record.fields.len());
+                for (k, v) in record.fields.drain() {
+                    let name = self.rename_field(&k).unwrap_or(k);
+                    fields.insert(name, v);
+                }
+                record.fields = fields;
+                if let Some(key) = record.record_key.as_mut() {
+                    for (name, _) in key.0.iter_mut() {
+                        if let Some(new_name) = self.rename_field(name) {
+                            *name = new_name;
+                        }
+                    }
+                }
+            }
+            Transform::DropEmpty => record.fields.retain(|_, v| match v {
+                Value::Single(s) => !s.is_empty(),
+                Value::List(l) => !l.is_empty(),
+            }),
+            Transform::Trim => {
+                for v in record.fields.values_mut() {
+                    match v {
+                        Value::Single(s) => *s = s.trim().to_string(),
+                        Value::List(l) => {
+                            for item in l.iter_mut() {
+                                *item = item.trim().to_string();
+                            }
+                        }
+                    }
+                }
+            }
+            Transform::Split { field, separator } => {
+                if let Some(v) = record.fields.get_mut(field) {
+                    let items: Vec<&str> = match v {
+                        Value::Single(s) => vec![s.as_str()],
+                        Value::List(l) => l.iter().map(|s| s.as_str()).collect(),
+                    };
+                    let parts = items
+                        .into_iter()
+                        .flat_map(|s| separator.split(s))
+                        .filter(|p| !p.is_empty())
+                        .map(|p| p.to_string())
+                        .collect();
+                    *v = Value::List(parts);
+                }
+            }
+            Transform::Map(func) => func(record),
+        }
+    }
+}
+
+/// An ordered list of transforms applied to each parsed record.
+#[derive(Debug, Clone, Default)]
+pub struct Pipeline {
+    /// The steps, in the order they are applied.
+    pub steps: Vec<Transform>,
+}
+
+impl Pipeline {
+    /// Creates an empty pipeline, which leaves records unchanged.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns `true` if the pipeline has no steps.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Appends a step.
+    pub fn then(mut self, step: Transform) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Appends all steps of another pipeline.
+    pub fn extend(mut self, other: Pipeline) -> Self {
+        self.steps.extend(other.steps);
+        self
+    }
+
+    /// Converts field names to lowercase.
+    pub fn lowercase_keys(self) -> Self {
+        self.then(Transform::LowercaseKeys)
+    }
+
+    /// Converts field names to uppercase.
+    pub fn uppercase_keys(self) -> Self {
+        self.then(Transform::UppercaseKeys)
+    }
+
+    /// Renames fields using `(from, to)` pairs.
+    pub fn rename<I, S, T>(self, names: I) -> Self
+    where
+        I: IntoIterator<Item = (S, T)>,
+        S: Into<String>,
+        T: Into<String>,
+    {
+        self.then(Transform::Rename(
+            names
+                .into_iter()
+                .map(|(from, to)| (from.into(), to.into()))
+                .collect(),
+        ))
+    }
+
+    /// Renames common vendor names to the normalized capture group names, see
+    /// [`COMMON_CAPTURE_GROUP_SYNONYMS`]. Lowercase names are renamed as well.
+    pub fn rename_common(self) -> Self {
+        self.rename(COMMON_CAPTURE_GROUP_SYNONYMS.iter().flat_map(|(from, to)| {
+            [
+                (from.to_string(), to.to_string()),
+                (from.to_lowercase(), to.to_lowercase()),
+            ]
+        }))
+    }
+
+    /// Removes fields with an empty value.
+    pub fn drop_empty(self) -> Self {
+        self.then(Transform::DropEmpty)
+    }
+
+    /// Trims whitespace around values.
+    pub fn trim(self) -> Self {
+        self.then(Transform::Trim)
+    }
+
+    /// Splits `field` into a list on the `separator` regex.
+    pub fn split(self, field: &str, separator: &str) -> Result<Self> {
+        let separator = Regex::new(separator).map_err(|e| {
+            TextFsmError::ParseError(format!("invalid separator for {}: {}", field, e))
+        })?;
+        Ok(self.then(Transform::Split {
+            field: field.to_string(),
+            separator,
+        }))
+    }
+
+    /// Calls `func` with each record.
+    pub fn map<F>(self, func: F) -> Self
+    where
+        F: Fn(&mut DataRecord) + Send + Sync + 'static,
+    {
+        self.then(Transform::Map(Arc::new(func)))
+    }
+
+    /// Applies the steps to a record.
+    ///
+    /// The values in `record_key` follow the renamed and transformed fields.
+    pub fn apply(&self, mut record: DataRecord) -> DataRecord {
+        if self.steps.is_empty() {
+            return record;
+        }
+        for step in &self.steps {
+            step.apply(&mut record);
+        }
+        if let Some(key) = record.record_key.as_mut() {
+            for (name, value) in key.0.iter_mut() {
+                if let Some(v) = record.fields.get(name) {
+                    *value = v.clone();
+                }
+            }
+        }
+        record
+    }
+
+    /// Applies the steps to each record.
+    pub fn apply_all<I: IntoIterator<Item = DataRecord>>(&self, records: I) -> Vec<DataRecord> {
+        records.into_iter().map(|r| self.apply(r)).collect()
+    }
+
+    /// Returns the field names after renaming, for a header of Value names.
+    pub fn header(&self, names: &[String]) -> Vec<String> {
+        let mut header: Vec<String> = vec![];
+        for name in names {
+            let name = self
+                .steps
+                .iter()
+                .fold(name.clone(), |n, step| step.rename_field(&n).unwrap_or(n));
+            if !header.contains(&name) {
+                header.push(name);
+            }
+        }
+        header
+    }
+}
+
+impl From<DataRecordConversion> for Pipeline {
+    fn from(conversion: DataRecordConversion) -> Self {
+        match conversion {
+            DataRecordConversion::LowercaseKeys => Pipeline::new().lowercase_keys(),
+        }
+    }
+}
+
+impl From<Option<DataRecordConversion>> for Pipeline {
+    fn from(conversion: Option<DataRecordConversion>) -> Self {
+        conversion.map(Pipeline::from).unwrap_or_default()
+    }
+}
@@ -0,0 +1,203 @@
+//! XML element names and the XSD of XML exports.
+//!
+//! Value names such as `5MIN_CPU`, `IN/OUT` or `ifc:name` are not valid XML
+//! names. [`element_name`] replaces the invalid characters, adding a numeric
+//! suffix when the result is already another field's name; the XML export
+//! then keeps the original in a `name` attribute:
+//!
+//! ```text
+//! <_5MIN_CPU name="5MIN_CPU">3%</_5MIN_CPU>
+//! ```
+//!
+//! [`TextFSM::xml_schema`] describes the XML export of a template's records.
+
+use crate::export::ExportOptions;
+use crate::{DataRecordConversion, TextFSM};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+fn is_name_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.')
+}
+
+/// Returns a valid XML element name for `name`.
+///
+/// Characters not allowed in names (including `:`, reserved for namespaces)
+/// become `_`, and `_` is prepended to names that start with a digit, `-` or
+/// `.`, or with `xml` in any case, which is reserved.
+pub fn element_name(name: &str) -> Cow<'_, str> {
+    let valid = name.starts_with(is_name_start)
+        && name.chars().all(is_name_char)
+        && !name.to_ascii_lowercase().starts_with("xml");
+    if valid {
+        return Cow::Borrowed(name);
+    }
+    let mut out: String = name
+        .chars()
+        .map(|c| if is_name_char(c) { c } else { '_' })
+        .collect();
+    if !out.starts_with(is_name_start) || out.to_ascii_lowercase().starts_with("xml") {
+        out.insert(0, '_');
+    }
+    Cow::Owned(out)
+}
+
+/// Returns unique element names for `names`, in the same order.
+///
+/// Names that are valid XML names keep them. The others get their
+/// [`element_name`], with a numeric suffix (`_2`, `_3`, ...) when that is
+/// taken, e.g. `IN/OUT` becomes `IN_OUT_2` next to `IN_OUT`. The result only
+/// depends on the set of names, not their order.
+pub(crate) fn element_names(names: &[&str]) -> Vec<String> {
+    let mut sorted: Vec<&str> = names.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    let mut used: HashSet<String> = sorted
+        .iter()
+        .filter(|n| matches!(element_name(n), Cow::Borrowed(_)))
+        .map(|n| n.to_string())
+        .collect();
+    let mut tags: HashMap<&str, String> = HashMap::new();
+    for name in sorted {
+        let tag = match element_name(name) {
+            Cow::Borrowed(tag) => tag.to_string(),
+            Cow::Owned(tag) => {
+                let mut unique = tag.clone();
+                let mut n = 1;
+                while used.contains(&unique) {
+                    n += 1;
+                    unique = format!("{}_{}", tag, n);
+                }
+                used.insert(unique.clone());
+                unique
+            }
+        };
+        tags.insert(name, tag);
+    }
+    names.iter().map(|n| tags[n].clone()).collect()
+}
+
+/// Escapes text for element content and attribute values. Characters XML 1.0
+/// does not allow, such as most C0 controls, become U+FFFD.
+pub(crate) fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            '\u{0}'..='\u{1f}' | '\u{fffe}' | '\u{ffff}' => out.push('\u{fffd}'),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+impl TextFSM {
+    /// Returns an XSD for the XML export of this template's records, with the
+    /// element names the pipeline, `conversion` and `options` give them.
+    ///
+    /// Every Value is an optional element of the record except `Required`
+    /// ones; `List` Values hold `item` elements.
+    pub fn xml_schema(
+        &self,
+        conversion: Option<DataRecordConversion>,
+        options: &ExportOptions,
+    ) -> String {
+        let pipeline = self.output_pipeline(conversion);
+        let mut fields: Vec<(String, bool, bool)> = vec![];
+        for def in self.parser.value_definitions() {
+            let name = pipeline.header(std::slice::from_ref(&def.name)).remove(0);
+            if !fields.iter().any(|(n, _, _)| n == &name) {
+                fields.push((name, def.is_list, def.is_required));
+            }
+        }
+        fields.sort();
+        let names: Vec<&str> = fields.iter().map(|(n, _, _)| n.as_str()).collect();
+        let tags = element_names(&names);
+
+        let mut xsd = String::new();
+        xsd.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xsd.push_str("<xs:schema xmlns:xs=\"http://www.w3.org/2001/XMLSchema\" elementFormDefault=\"qualified\">\n");
+        xsd.push_str(&format!(
+            "  <xs:element name=\"{}\">\n    <xs:complexType>\n      <xs:sequence>\n",
+            element_name(&options.xml_root)
+        ));
+        xsd.push_str(&format!(
+            "        <xs:element name=\"{}\" minOccurs=\"0\" maxOccurs=\"unbounded\">\n",
+            element_name(&options.xml_record)
+        ));
+        xsd.push_str("          <xs:complexType>\n            <xs:all>\n");
+        for ((name, is_list, is_required), tag) in fields.iter().zip(&tags) {
+            let min = if *is_required { 1 } else { 0 };
+            // The original name, for elements that had to be renamed
+            let attribute = (tag != name).then(|| {
+                format!(
+                    "<xs:attribute name=\"name\" type=\"xs:string\" fixed=\"{}\"/>",
+                    escape(name)
+                )
+            });
+            match (is_list, attribute) {
+                (false, None) => xsd.push_str(&format!(
+                    "              <xs:element name=\"{}\" type=\"xs:string\" minOccurs=\"{}\"/>\n",
+                    tag, min
+                )),
+                (false, Some(attribute)) => xsd.push_str(&format!(
+                    concat!(
+                        "              <xs:element name=\"{}\" minOccurs=\"{}\">\n",
+                        "                <xs:complexType>\n",
+                        "                  <xs:simpleContent>\n",
+                        "                    <xs:extension base=\"xs:string\">\n",
+                        "                      {}\n",
+                        "                    </xs:extension>\n",
+                        "                  </xs:simpleContent>\n",
+                        "                </xs:complexType>\n",
+                        "              </xs:element>\n",
+                    ),
+                    tag, min, attribute
+                )),
+                (true, attribute) => {
+                    xsd.push_str(&format!(
+                        concat!(
+                            "              <xs:element name=\"{}\" minOccurs=\"{}\">\n",
+                            "                <xs:complexType>\n",
+                            "                  <xs:sequence>\n",
+                            "                    <xs:element name=\"item\" type=\"xs:string\" minOccurs=\"0\" maxOccurs=\"unbounded\"/>\n",
+                            "                  </xs:sequence>\n",
+                        ),
+                        tag, min
+                    ));
+                    if let Some(attribute) = attribute {
+                        xsd.push_str(&format!("                  {}\n", attribute));
+                    }
+                    xsd.push_str(
+                        "                </xs:complexType>\n              </xs:element>\n",
+                    );
+                }
+            }
+        }
+        xsd.push_str("            </xs:all>\n");
+        for attribute in ["start_line", "end_line"] {
+            xsd.push_str(&format!(
+                "            <xs:attribute name=\"{}\" type=\"xs:positiveInteger\"/>\n",
+                attribute
+            ));
+        }
+        xsd.push_str("          </xs:complexType>\n        </xs:element>\n      </xs:sequence>\n");
+        for attribute in ["template", "platform", "command", "source"] {
+            xsd.push_str(&format!(
+                "      <xs:attribute name=\"{}\" type=\"xs:string\"/>\n",
+                attribute
+            ));
+        }
+        xsd.push_str("    </xs:complexType>\n  </xs:element>\n</xs:schema>\n");
+        xsd
+    }
+}
@@ -0,0 +1,119 @@
+#![cfg(feature = "arrow")]
+
+use arrow_array::Array;
+use arrow_array::cast::AsArray;
+use arrow_array::types::UInt16Type;
+use arrow_schema::DataType;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use textfsm_rs::arrow::{infer_schema, record_batch};
+use textfsm_rs::export::RecordSink;
+use textfsm_rs::schema::Schema;
+use textfsm_rs::{DataRecordConversion, OutputFormat, TextFSM, TextFsmExport};
+
+const TEMPLATE: &str = r"Value Key INTERFACE (\S+)
+Value VLAN_ID (\d+)
+Value List MEMBERS (\S+)
+
+Start
+  ^Interface ${INTERFACE}
+  ^  vlan ${VLAN_ID}
+  ^  member ${MEMBERS}
+  ^! -> Record
+";
+
+const INPUT: &str = "Interface Po1
+  vlan 10
+  member Gi0/1
+  member Gi0/2
+!
+Interface Lo0
+!
+";
+
+#[test]
+fn test_schema_from_template() {
+    let fsm = TextFSM::from_string(TEMPLATE)
+        .unwrap()
+        .with_schema(Schema::common());
+    let schema = fsm.arrow_schema(Some(DataRecordConversion::LowercaseKeys));
+    let names: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+    assert_eq!(names, vec!["interface", "vlan_id", "members"]);
+    assert_eq!(schema.field(0).data_type(), &DataType::Utf8);
+    assert_eq!(schema.field(1).data_type(), &DataType::UInt16);
+    assert!(matches!(schema.field(2).data_type(), DataType::List(_)));
+
+    // Without a schema every column is text
+    let fsm = TextFSM::from_string(TEMPLATE).unwrap();
+    assert_eq!(fsm.arrow_schema(None).field(1).data_type(), &DataType::Utf8);
+}
+
+#[test]
+fn test_record_batch() {
+    let mut fsm = TextFSM::from_string(TEMPLATE)
+        .unwrap()
+        .with_schema(Schema::common());
+    let records = fsm.parse_string(INPUT, None).unwrap();
+    let batch = record_batch(&records, fsm.arrow_schema(None)).unwrap();
+    assert_eq!(batch.num_rows(), 2);
+
+    let vlans = batch.column(1).as_primitive::<UInt16Type>();
+    assert_eq!(vlans.value(0), 10);
+    assert!(vlans.is_null(1));
+
+    let members = batch.column(2).as_list::<i32>();
+    let first = members.value(0);
+    let first = first.as_string::<i32>();
+    assert_eq!(first.value(0), "Gi0/1");
+    assert_eq!(first.value(1), "Gi0/2");
+    assert_eq!(members.value(1).len(), 0);
+
+    // Records are not checked against the schema unless it is set, so a bad
+    // value only shows up on conversion
+    let mut fsm = TextFSM::from_string(TEMPLATE).unwrap();
+    let records = fsm
+        .parse_string("Interface Po2\n  vlan 99999\n!\n", None)
+        .unwrap();
+    let typed = TextFSM::from_string(TEMPLATE)
+        .unwrap()
+        .with_schema(Schema::common())
+        .arrow_schema(None);
+    assert!(record_batch(&records, typed).is_err());
+}
+
+#[test]
+fn test_export_parquet() {
+    let mut fsm = TextFSM::from_string(TEMPLATE).unwrap();
+    let records = fsm.parse_string(INPUT, None).unwrap();
+    assert!(records.export(OutputFormat::Parquet).is_err());
+
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    records.export_to(OutputFormat::Parquet, &mut file).unwrap();
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file.reopen().unwrap())
+        .unwrap()
+        .build()
+        .unwrap();
+    let batches: Vec<_> = reader.map(|b| b.unwrap()).collect();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].schema(), infer_schema(&records));
+    assert_eq!(
+        batches[0],
+        record_batch(&records, infer_schema(&records)).unwrap()
+    );
+
+    // The sink writes the template's schema when given one
+    let mut sink =
+        RecordSink::new(vec![], OutputFormat::Parquet).with_arrow_schema(fsm.arrow_schema(None));
+    for rec in &records {
+        sink.write_record(rec).unwrap();
+    }
+    let data = bytes_file(sink.finish().unwrap());
+    let reader = ParquetRecordBatchReaderBuilder::try_new(data.reopen().unwrap()).unwrap();
+    assert_eq!(reader.schema().as_ref(), fsm.arrow_schema(None).as_ref());
+}
+
+fn bytes_file(data: Vec<u8>) -> tempfile::NamedTempFile {
+    use std::io::Write;
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(&data).unwrap();
+    file
+}
@@ -159,3 +159,350 @@ fn test_clitable_parse_fail() {
     let result = CliTable::from_file(index_path);
     assert!(result.is_err());
 }
+
+#[test]
+#[cfg(feature = "clitable")]
+fn test_clitable_layered_indexes() {
+    use textfsm_rs::CliTable;
+    let site_index = "tests/basic_template/site/index";
+    let base_index = "tests/basic_template/template/parseindex_index";
+    let cli_table =
+        CliTable::from_files([site_index, base_index]).expect("Failed to load CLI table indexes");
+    assert_eq!(cli_table.tables.len(), 2);
+
+    // The site layer overrides the base row for the same command.
+    let (dir, row) = cli_table
+        .get_template_for_command("VendorA", "show version")
+        .unwrap();
+    assert_eq!(dir, "tests/basic_template/site");
+    assert_eq!(row.templates, vec!["site_templateA"]);
+
+    // The site layer extends the base index with new commands.
+    let (dir, row) = cli_table
+        .get_template_for_command("VendorA", "sh cl")
+        .unwrap();
+    assert_eq!(dir, "tests/basic_template/site");
+    assert_eq!(row.templates, vec!["site_templateE"]);
+
+    // Commands only in the base layer resolve relative to the base index.
+    let (dir, row) = cli_table
+        .get_template_for_command("VendorA", "sh in")
+        .unwrap();
+    assert_eq!(dir, "tests/basic_template/template");
+    assert_eq!(row.templates, vec!["clitable_templateD"]);
+
+    // Adding the layers the other way round gives the base index priority.
+    let mut cli_table = CliTable::from_file(base_index).unwrap();
+    cli_table.add_index(site_index).unwrap();
+    let (_, row) = cli_table
+        .get_template_for_command("VendorA", "show version")
+        .unwrap();
+    assert_eq!(
+        row.templates,
+        vec!["clitable_templateA", "clitable_templateB"]
+    );
+
+    // A failing layer leaves the table untouched.
+    let index_path = "tests/basic_template/template/parseindexfail1_index";
+    assert!(cli_table.add_index(index_path).is_err());
+    assert_eq!(cli_table.tables.len(), 2);
+}
+
+#[test]
+#[cfg(feature = "clitable")]
+fn test_clitable_template_cache() {
+    use std::sync::Arc;
+    use textfsm_rs::{CliTable, TextFSM};
+    let index_path = "tests/basic_template/template/parseindex_index";
+    let cli_table = CliTable::from_file(index_path).unwrap();
+    assert!(cli_table.template_cache.is_empty());
+
+    let parsers = cli_table
+        .get_compiled_templates_for_command("VendorA", "sh ve")
+        .unwrap()
+        .expect("command should match");
+    assert_eq!(parsers.len(), 2);
+    assert_eq!(cli_table.template_cache.len(), 2);
+
+    // A second lookup is served from the cache, including through a clone.
+    let cloned = cli_table.clone();
+    let again = cloned
+        .get_compiled_templates_for_command("VendorA", "show version")
+        .unwrap()
+        .unwrap();
+    assert!(Arc::ptr_eq(&parsers[0], &again[0]));
+    assert!(Arc::ptr_eq(&parsers[1], &again[1]));
+
+    let mut fsm = TextFSM::from_shared(parsers[1].clone());
+    assert!(Arc::ptr_eq(&fsm.parser, &parsers[1]));
+    let result = fsm.parse_string("a b\n", None).unwrap();
+    assert_eq!(result[0].get("Col4").unwrap().to_string(), "b");
+
+    assert!(
+        cli_table
+            .get_compiled_templates_for_command("VendorA", "sh foo")
+            .unwrap()
+            .is_none()
+    );
+
+    // Everything referenced by the index compiles.
+    assert!(cli_table.precompile_templates().is_empty());
+    assert_eq!(cli_table.template_cache.len(), 4);
+}
+
+#[test]
+#[cfg(feature = "clitable")]
+fn test_clitable_precompile_reports_all_failures() {
+    use textfsm_rs::CliTable;
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("index"),
+        "Template, Vendor, Command\n\
+         good, VendorA, sh[[ow]] ve[[rsion]]\n\
+         missing, VendorA, sh[[ow]] cl[[ock]]\n\
+         broken:good, VendorA, sh[[ow]] in[[terfaces]]\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("good"),
+        "Value A (\\S+)\n\nStart\n  ^${A} -> Record\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("broken"),
+        "Value A (\\S+)\n\nStart\n  ^${B} -> Record\n",
+    )
+    .unwrap();
+
+    let cli_table = CliTable::from_file(dir.path().join("index")).unwrap();
+    let failures = cli_table.precompile_templates();
+    let mut failed: Vec<String> = failures
+        .iter()
+        .map(|(p, _)| p.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    failed.sort();
+    assert_eq!(failed, vec!["broken", "missing"]);
+    assert_eq!(cli_table.template_cache.len(), 1);
+}
+
+#[test]
+#[cfg(feature = "clitable")]
+fn test_clitable_reverse_lookup() {
+    use textfsm_rs::CliTable;
+    let cli_table = CliTable::from_files([
+        "tests/basic_template/site/index",
+        "tests/basic_template/template/parseindex_index",
+    ])
+    .unwrap();
+
+    assert_eq!(cli_table.platforms(), vec!["VendorA", "VendorB"]);
+
+    let commands = cli_table.commands_for_platform("VendorA");
+    let listed: Vec<(&str, &str)> = commands
+        .iter()
+        .map(|c| (c.command.as_str(), c.full_command.as_str()))
+        .collect();
+    assert_eq!(
+        listed,
+        vec![
+            ("sh[[ow]] ve[[rsion]]", "show version"),
+            ("sh[[ow]] cl[[ock]]", "show clock"),
+            ("sh[[ow]] ve[[rsion]]", "show version"),
+            ("sh[[ow]] in[[terfaces]]", "show interfaces"),
+        ]
+    );
+    assert_eq!(commands[0].template_dir, "tests/basic_template/site");
+    assert_eq!(commands[3].templates, vec!["clitable_templateD"]);
+    assert!(cli_table.commands_for_platform("VendorC").is_empty());
+
+    assert_eq!(
+        cli_table.platforms_for_command("show version"),
+        vec!["VendorA", "VendorB"]
+    );
+    assert_eq!(cli_table.platforms_for_command("sh cl"), vec!["VendorA"]);
+    assert!(cli_table.platforms_for_command("reload").is_empty());
+
+    // Values of all templates of the row, in declaration order, without duplicates.
+    let cli_table = CliTable::from_file("tests/basic_template/template/parseindex_index").unwrap();
+    let values = cli_table
+        .template_values_for_command("VendorA", "show version")
+        .unwrap()
+        .unwrap();
+    let names: Vec<&str> = values.iter().map(|v| v.name()).collect();
+    assert_eq!(names, vec!["Col1", "Col2", "Col3", "Col4"]);
+    assert!(values[0].is_key());
+    assert!(!values[1].is_key());
+    assert!(
+        cli_table
+            .template_values_for_command("VendorA", "reload")
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[test]
+#[cfg(feature = "clitable")]
+fn test_clitable_resolve_command() {
+    use textfsm_rs::source::MemorySource;
+    use textfsm_rs::{CliTable, TextFsmError};
+    let mut source = MemorySource::new("resolve");
+    source.insert(
+        "index",
+        "Template, Platform, Command\n\
+         ip_int_brief, os, sh[[ow]] ip int[[erface]] br[[ief]]\n\
+         interfaces, os, sh[[ow]] i[[nterfaces]]\n\
+         inventory, os, sh[[ow]] in[[ventory]]\n\
+         ip_route, os, sh[[ow]] i[[p route]]\n",
+    );
+    let mut cli_table = CliTable::from_source(source).unwrap();
+
+    // Whitespace and case are normalized.
+    let m = cli_table
+        .resolve_command("os", "  SHOW   IP  int  brief ")
+        .unwrap();
+    assert_eq!(m.command.templates, vec!["ip_int_brief"]);
+
+    // All matches are returned, most specific first.
+    let matches = cli_table.matching_commands("os", "sh in");
+    let templates: Vec<&str> = matches
+        .iter()
+        .map(|m| m.command.templates[0].as_str())
+        .collect();
+    assert_eq!(templates, vec!["inventory", "interfaces"]);
+    let m = cli_table.resolve_command("os", "sh in").unwrap();
+    assert_eq!(m.command.full_command, "show inventory");
+
+    // Equally specific matches for different commands are ambiguous.
+    match cli_table.resolve_command("os", "sh i") {
+        Err(TextFsmError::CommandError(msg)) => {
+            assert!(msg.contains("Ambiguous"), "{}", msg);
+            assert!(msg.contains("'show interfaces'"), "{}", msg);
+            assert!(msg.contains("'show ip route'"), "{}", msg);
+        }
+        x => panic!("expected ambiguity, got {:?}", x),
+    }
+    // The first-hit lookup is unchanged.
+    let (_, row) = cli_table.get_template_for_command("os", "sh i").unwrap();
+    assert_eq!(row.templates, vec!["interfaces"]);
+
+    // Near misses get a suggestion, unrelated commands do not.
+    assert_eq!(
+        cli_table.suggest_command("os", "show ip intreface brief"),
+        Some("show ip interface brief".to_string())
+    );
+    match cli_table.resolve_command("os", "show ip intreface brief") {
+        Err(TextFsmError::CommandError(msg)) => {
+            assert!(
+                msg.ends_with("did you mean 'show ip interface brief'?"),
+                "{}",
+                msg
+            )
+        }
+        x => panic!("expected a miss, got {:?}", x),
+    }
+    assert_eq!(cli_table.suggest_command("os", "reload"), None);
+
+    // Index commands written with capitals resolve from any case.
+    let mut mixed = MemorySource::new("mixed");
+    mixed.insert(
+        "index",
+        "Template, Platform, Command
+version, vendor, Sh[[ow]] Ver[[sion]]
+",
+    );
+    let mixed = CliTable::from_source(mixed).unwrap();
+    for cmd in ["Sh Ver", "show version", "SHOW VER"] {
+        let m = mixed.resolve_command("vendor", cmd).unwrap();
+        assert_eq!(m.command.full_command, "Show Version");
+        assert!(mixed.get_template_for_command("vendor", cmd).is_some());
+    }
+    assert_eq!(
+        mixed.suggest_command("vendor", "show versoin"),
+        Some("Show Version".to_string())
+    );
+
+    // Overriding a command in a lower layer does not make it ambiguous.
+    let mut overrides = MemorySource::new("base");
+    overrides.insert(
+        "index",
+        "Template, Platform, Command\nbase_inventory, os, sh[[ow]] in[[ventory]]\n",
+    );
+    cli_table.add_source(overrides).unwrap();
+    let m = cli_table.resolve_command("os", "show inventory").unwrap();
+    assert_eq!(m.command.templates, vec!["inventory"]);
+    assert_eq!(cli_table.matching_commands("os", "show inventory").len(), 2);
+}
+
+#[test]
+#[cfg(feature = "clitable")]
+fn test_clitable_validate() {
+    use textfsm_rs::CliTable;
+    use textfsm_rs::cli_table::IndexIssueKind;
+    use textfsm_rs::source::MemorySource;
+    let mut source = MemorySource::new("check");
+    source
+        .insert(
+            "index",
+            "Template, Platform, Command, Comment\n\
+             version, os, sh[[ow]] ver[[sion]],\n\
+             interfaces, os, sh[[ow]] int[[erfaces]],\n\
+             interfaces_brief, os, sh[[ow]] int[[erfaces]] br[[ief]],\n\
+             version, os, sh[[ow]] ver[[sion]],\n\
+             interfaces, os, show interfaces,\n\
+             version, other, sh[[ow]] ver[[sion]],\n\
+             missing, os, sh[[ow]] log[[ging]],\n\
+             broken, os, sh[[ow]] clock]],\n",
+        )
+        .insert(
+            "version",
+            "Value VERSION (\\S+)\n\nStart\n  ^${VERSION} -> Record\n",
+        )
+        .insert(
+            "interfaces",
+            "Value NAME (\\S+)\n\nStart\n  ^${NAME} -> Record\n",
+        )
+        .insert(
+            "interfaces_brief",
+            "Value NAME (\\S+)\n\nStart\n  ^${NAME} -> Record\n",
+        )
+        .insert(
+            "broken",
+            "Value NAME (\\S+\n\nStart\n  ^${NAME} -> Record\n",
+        );
+    let cli_table = CliTable::from_source(source).unwrap();
+
+    let issues = cli_table.validate();
+    let found: Vec<(IndexIssueKind, Option<usize>)> =
+        issues.iter().map(|i| (i.kind, i.row)).collect();
+    assert_eq!(
+        found,
+        vec![
+            (IndexIssueKind::UnknownColumn, None),
+            (IndexIssueKind::DuplicateCommand, Some(4)),
+            (IndexIssueKind::ShadowedCommand, Some(5)),
+            (IndexIssueKind::MissingTemplate, Some(7)),
+            (IndexIssueKind::UnbalancedBrackets, Some(8)),
+            (IndexIssueKind::BrokenTemplate, Some(8)),
+        ],
+        "{:#?}",
+        issues
+    );
+    assert!(issues[0].message.contains("'Comment'"));
+    assert!(!issues[0].kind.is_error());
+    assert!(issues[2].message.contains("row 2"), "{}", issues[2].message);
+
+    // An unclosed `[[` usually is not even a valid regex; loading names the row.
+    let mut source = MemorySource::new("unclosed");
+    source.insert(
+        "index",
+        "Template, Platform, Command\nversion, os, sh[[ow]] ver[[sion\n",
+    );
+    match CliTable::from_source(source) {
+        Err(e) => assert!(e.to_string().contains("row 1"), "{}", e),
+        Ok(_) => panic!("expected an invalid command"),
+    }
+
+    // A clean index has no issues.
+    let cli_table = CliTable::from_file("tests/basic_template/site/index").unwrap();
+    assert_eq!(cli_table.validate(), vec![]);
+}
@@ -0,0 +1,92 @@
+use textfsm_rs::diff::{DiffOptions, RecordChange, diff_records};
+use textfsm_rs::{DataRecord, TextFSM, Value};
+
+const TEMPLATE: &str = "Value Key INTERFACE (\\S+)\nValue STATUS (\\S+)\nValue MTU (\\d+)\n\nStart\n  ^${INTERFACE}\\s+${STATUS}\\s+${MTU} -> Record\n";
+
+fn parse(input: &str) -> Vec<DataRecord> {
+    let mut fsm = TextFSM::from_string(TEMPLATE).unwrap();
+    fsm.parse_string(input, None).unwrap()
+}
+
+#[test]
+fn test_diff_by_key_fields() {
+    let old = parse("Gi0/1 up 1500\nGi0/2 up 1500\nGi0/3 down 1500\n");
+    let new = parse("Gi0/4 up 1500\nGi0/2 down 9000\nGi0/1 up 1500\n");
+
+    let diff = diff_records(&old, &new, &DiffOptions::with_keys(&["INTERFACE"]));
+    assert_eq!(diff.changes.len(), 3);
+    match &diff.changes[0] {
+        RecordChange::Changed {
+            old_index,
+            new_index,
+            key,
+            fields,
+        } => {
+            assert_eq!((*old_index, *new_index), (1, 1));
+            assert_eq!(key.as_ref().unwrap().to_string(), "INTERFACE=Gi0/2");
+            let names: Vec<&str> = fields.iter().map(|f| f.field.as_str()).collect();
+            assert_eq!(names, vec!["MTU", "STATUS"]);
+            assert_eq!(fields[1].old, Some(Value::Single("up".to_string())));
+            assert_eq!(fields[1].new, Some(Value::Single("down".to_string())));
+        }
+        x => panic!("expected a changed record, got {:?}", x),
+    }
+    let removed: Vec<String> = diff
+        .removed()
+        .map(|r| r.get("INTERFACE").unwrap().to_string())
+        .collect();
+    assert_eq!(removed, vec!["Gi0/3"]);
+    let added: Vec<String> = diff
+        .added()
+        .map(|r| r.get("INTERFACE").unwrap().to_string())
+        .collect();
+    assert_eq!(added, vec!["Gi0/4"]);
+
+    let text = diff.to_unified();
+    assert!(
+        text.starts_with("~ [2 -> 2] INTERFACE=Gi0/2\n    MTU: \"1500\" -> \"9000\"\n    STATUS: \"up\" -> \"down\"\n- [3] {"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("\n+ [1] {INTERFACE: \"Gi0/4\", MTU: \"1500\", STATUS: \"up\"}"),
+        "{}",
+        text
+    );
+
+    // Without moves reported, reordering alone is not a difference.
+    assert!(DataRecord::diff(&old, &[old[2].clone(), old[0].clone(), old[1].clone()]).is_empty());
+}
+
+#[test]
+fn test_diff_reports_moves() {
+    let old = parse("Gi0/1 up 1500\nGi0/2 up 1500\n");
+    let new = parse("Gi0/2 up 1500\nGi0/1 up 1500\n");
+    let options = DiffOptions {
+        report_moves: true,
+        ..DiffOptions::with_keys(&["INTERFACE"])
+    };
+    let diff = diff_records(&old, &new, &options);
+    assert_eq!(diff.to_unified(), "> [1 -> 2] INTERFACE=Gi0/1");
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_diff_json() {
+    let old = parse("Gi0/1 up 1500\n");
+    let new = parse("Gi0/1 down 1500\n");
+    let diff = diff_records(&old, &new, &DiffOptions::with_keys(&["INTERFACE"]));
+    let json: serde_json::Value = serde_json::from_str(&diff.to_json().unwrap()).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "changes": [{
+                "change": "changed",
+                "old_index": 0,
+                "new_index": 0,
+                "key": {"INTERFACE": "Gi0/1"},
+                "fields": [{"field": "STATUS", "old": "up", "new": "down"}]
+            }]
+        })
+    );
+}
@@ -0,0 +1,108 @@
+use textfsm_rs::TextFSM;
+use textfsm_rs::embed::{self, EmbeddedTemplates};
+
+static SITE: EmbeddedTemplates = EmbeddedTemplates::new(
+    "site",
+    &[
+        ("index", include_str!("basic_template/site/index")),
+        (
+            "site_templateA",
+            include_str!("basic_template/site/site_templateA"),
+        ),
+        (
+            "site_templateE",
+            include_str!("basic_template/site/site_templateE"),
+        ),
+    ],
+);
+
+#[test]
+fn test_embedded_parser() {
+    assert!(SITE.get("index").is_some());
+    assert!(SITE.read("missing").is_err());
+
+    let mut fsm = TextFSM::from_parser(SITE.parser("site_templateE").unwrap());
+    let result = fsm.parse_string("12:00:01\n", None).unwrap();
+    assert_eq!(result[0].get("Time").unwrap().to_string(), "12:00:01");
+}
+
+#[test]
+#[cfg(feature = "clitable")]
+fn test_embedded_cli_table() {
+    use textfsm_rs::CliTable;
+    let cli_table = CliTable::from_embedded(&SITE).unwrap();
+
+    let (dir, row) = cli_table
+        .get_template_for_command("VendorA", "sh ve")
+        .unwrap();
+    assert_eq!(dir, "embedded:site");
+    assert_eq!(row.templates, vec!["site_templateA"]);
+
+    let parsers = cli_table
+        .get_compiled_templates_for_command("VendorA", "show clock")
+        .unwrap()
+        .unwrap();
+    assert_eq!(parsers.len(), 1);
+    assert!(cli_table.precompile_templates().is_empty());
+    assert_eq!(cli_table.template_cache.len(), 2);
+
+    // Embedded libraries layer with on-disk indexes like any other index.
+    let mut cli_table = CliTable::from_embedded(&SITE).unwrap();
+    cli_table
+        .add_index("tests/basic_template/template/parseindex_index")
+        .unwrap();
+    let (dir, _) = cli_table
+        .get_template_for_command("VendorA", "sh int")
+        .unwrap();
+    assert_eq!(dir, "tests/basic_template/template");
+}
+
+#[test]
+fn test_generate_embedding_module() {
+    let out_dir = tempfile::tempdir().unwrap();
+    let out_file =
+        embed::generate_to("site", "tests/basic_template/site", out_dir.path(), true).unwrap();
+    let module = std::fs::read_to_string(out_file).unwrap();
+
+    assert!(module.starts_with("// @generated"));
+    assert!(module.contains("::textfsm_rs::embed::EmbeddedTemplates::new(\"site\", &["));
+    assert!(module.contains("(\"index\", include_str!("));
+    assert!(module.contains("(\"site_templateA\", include_str!("));
+    assert!(module.contains("(\"site_templateE\", include_str!("));
+
+    assert!(
+        embed::generate_to(
+            "bad-name",
+            "tests/basic_template/site",
+            out_dir.path(),
+            false
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn test_generate_validation_reports_broken_templates() {
+    let src_dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        src_dir.path().join("one.textfsm"),
+        "Value A (\\S+)\n\nStart\n  ^${B}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        src_dir.path().join("two.textfsm"),
+        "Value A (\\S+)\n\nNotStart\n  ^${A}\n",
+    )
+    .unwrap();
+    // Files that are not templates are not compiled
+    std::fs::write(src_dir.path().join("README.md"), "# Templates\n").unwrap();
+    let out_dir = tempfile::tempdir().unwrap();
+
+    let err = embed::generate_to("lib", src_dir.path(), out_dir.path(), true)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("2 broken template(s)"), "{}", err);
+
+    // Without validation the library is embedded as-is.
+    assert!(embed::generate_to("lib", src_dir.path(), out_dir.path(), false).is_ok());
+}
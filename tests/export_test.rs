@@ -1,4 +1,5 @@
-use textfsm_rs::{OutputFormat, TextFSM, TextFsmExport};
+use textfsm_rs::export::RecordSink;
+use textfsm_rs::{ExportOptions, OutputFormat, TextFSM, TextFsmExport};
 
 fn get_results() -> Vec<textfsm_rs::DataRecord> {
     let template = r###"Value Name (\S+)
@@ -77,6 +78,26 @@ fn test_export_xml() {
     assert!(xml.contains("</results>"));
 }
 
+#[test]
+fn test_export_markdown() {
+    let results = get_results();
+    let md = results.export(OutputFormat::Markdown).unwrap();
+    assert_eq!(
+        md,
+        "| Age | Name |\n| --- | ---- |\n| 30 | Alice |\n| 25 | Bob |\n"
+    );
+}
+
+#[test]
+fn test_export_asciidoc() {
+    let results = get_results();
+    let adoc = results.export(OutputFormat::AsciiDoc).unwrap();
+    assert_eq!(
+        adoc,
+        "[options=\"header\"]\n|===\n| Age | Name\n\n| 30 | Alice\n| 25 | Bob\n|===\n"
+    );
+}
+
 #[test]
 fn test_export_escaping() {
     let template = r###"Value MSG (.+)
@@ -96,4 +117,224 @@ Start
     // HTML
     let html = results.export(OutputFormat::Html).unwrap();
     assert!(html.contains("&lt;Hello&gt; &amp; &quot;World&quot;"));
+
+    // Markdown and AsciiDoc cells end at `|`
+    let mut fsm = TextFSM::from_string(template).unwrap();
+    let results = fsm.parse_string("Message: up|down \\\n", None).unwrap();
+    let md = results.export(OutputFormat::Markdown).unwrap();
+    assert!(md.contains("| up\\|down \\\\ |"));
+    let adoc = results.export(OutputFormat::AsciiDoc).unwrap();
+    assert!(adoc.contains("| up\\|down \\\n"));
+}
+
+fn all_formats() -> Vec<OutputFormat> {
+    vec![
+        #[cfg(feature = "json")]
+        OutputFormat::Json,
+        #[cfg(feature = "json")]
+        OutputFormat::JsonLines,
+        #[cfg(feature = "yaml")]
+        OutputFormat::Yaml,
+        #[cfg(feature = "csv_export")]
+        OutputFormat::Csv,
+        OutputFormat::Text,
+        OutputFormat::Html,
+        OutputFormat::Markdown,
+        OutputFormat::AsciiDoc,
+        OutputFormat::Xml,
+    ]
+}
+
+#[test]
+fn test_export_to_writer() {
+    let results = get_results();
+    for format in all_formats() {
+        let mut out: Vec<u8> = vec![];
+        results.export_to(format, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            results.export(format).unwrap(),
+            "{:?}",
+            format
+        );
+    }
+}
+
+#[test]
+fn test_export_without_record_key() {
+    let template = "Value Key Name (\\S+)\nValue Age (\\d+)\n\nStart\n  ^Name: ${Name}\n  ^Age: ${Age} -> Record\n";
+    let mut fsm = TextFSM::from_string(template).unwrap();
+    let results = fsm.parse_string("Name: Alice\nAge: 30\n", None).unwrap();
+    assert!(results[0].record_key.is_some());
+    // The key repeats the Key fields, so no format writes it
+    for format in all_formats() {
+        let out = results.export(format).unwrap();
+        assert!(!out.contains("record_key"), "{:?}: {}", format, out);
+    }
+    #[cfg(feature = "json")]
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(
+            &results.export(OutputFormat::JsonLines).unwrap()
+        )
+        .unwrap(),
+        serde_json::json!({"Name": "Alice", "Age": "30"})
+    );
+}
+
+#[test]
+fn test_record_sink_matches_export() {
+    let template = "Value Name (\\S+)\nValue Age (\\d+)\n\nStart\n  ^Name: ${Name}\n  ^Age: ${Age} -> Record\n";
+    let data = "Name: Alice\nAge: 30\nName: Bob\nAge: 25\n";
+    for format in all_formats() {
+        let fsm = TextFSM::from_string(template).unwrap();
+        let mut sink = RecordSink::new(vec![], format);
+        let mut records = vec![];
+        for record in fsm.parse_reader(std::io::Cursor::new(data)) {
+            let record = record.unwrap();
+            sink.write_record(&record).unwrap();
+            records.push(record);
+        }
+        assert_eq!(sink.count(), 2);
+        let streamed = String::from_utf8(sink.finish().unwrap()).unwrap();
+        assert_eq!(streamed, records.export(format).unwrap(), "{:?}", format);
+    }
+
+    // An empty stream still produces a well-formed document.
+    let empty: Vec<textfsm_rs::DataRecord> = vec![];
+    for format in all_formats() {
+        let streamed = RecordSink::new(vec![], format).finish().unwrap();
+        assert_eq!(
+            String::from_utf8(streamed).unwrap(),
+            empty.export(format).unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_record_sink_text_columns_from_all_records() {
+    use textfsm_rs::DataRecord;
+
+    let mut first = DataRecord::new();
+    first.insert("Name".into(), "Alice".into());
+    let mut second = DataRecord::new();
+    second.insert("Name".into(), "Bob".into());
+    second.insert("Age".into(), "25".into());
+    let records = vec![first, second];
+
+    let mut sink = RecordSink::new(vec![], OutputFormat::Text);
+    for rec in &records {
+        sink.write_record(rec).unwrap();
+    }
+    let streamed = String::from_utf8(sink.finish().unwrap()).unwrap();
+    assert!(streamed.contains("Age"), "{}", streamed);
+    assert_eq!(streamed, records.export(OutputFormat::Text).unwrap());
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_export_json_lines() {
+    let results = get_results();
+    let jsonl = results.export(OutputFormat::JsonLines).unwrap();
+    let lines: Vec<&str> = jsonl.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(jsonl.ends_with('\n'));
+    for line in lines {
+        let value: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert!(value.get("Name").is_some());
+    }
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_json_lines_metadata() {
+    use textfsm_rs::LineRange;
+    use textfsm_rs::export::RecordMetadata;
+
+    let metadata = RecordMetadata {
+        template: Some("people.textfsm".into()),
+        source: Some("people.txt".into()),
+        ..Default::default()
+    };
+    let mut sink = RecordSink::new(vec![], OutputFormat::JsonLines).with_metadata(metadata);
+    let results = get_results();
+    sink.write_record_at(&results[0], Some(LineRange { start: 1, end: 2 }))
+        .unwrap();
+    sink.write_record(&results[1]).unwrap();
+    let out = String::from_utf8(sink.finish().unwrap()).unwrap();
+
+    let lines: Vec<serde_json::Value> = out
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["template"], "people.textfsm");
+    assert_eq!(lines[0]["source"], "people.txt");
+    assert!(lines[0].get("platform").is_none());
+    assert_eq!(lines[0]["lines"]["start"], 1);
+    assert_eq!(lines[0]["lines"]["end"], 2);
+    assert_eq!(lines[0]["record"]["Name"], "Alice");
+    assert!(lines[1].get("lines").is_none());
+    assert_eq!(lines[1]["record"]["Name"], "Bob");
+}
+
+fn get_list_results() -> Vec<textfsm_rs::DataRecord> {
+    let template = r###"Value Key Name (\S+)
+Value List Vlans (\d+)
+
+Start
+  ^Name: ${Name}
+  ^Vlan: ${Vlans}
+  ^! -> Record
+"###;
+
+    let data = "Name: Gi1\nVlan: 10\nVlan: 20\n!\nName: Gi2\n!\n";
+
+    let mut fsm = TextFSM::from_string(template).unwrap();
+    fsm.parse_string(data, None).unwrap()
+}
+
+#[test]
+fn test_export_lists() {
+    let results = get_list_results();
+
+    let xml = results.export(OutputFormat::Xml).unwrap();
+    assert!(
+        xml.contains("    <Vlans>\n      <item>10</item>\n      <item>20</item>\n    </Vlans>")
+    );
+    assert!(xml.contains("    <Vlans></Vlans>"));
+
+    let html = results.export(OutputFormat::Html).unwrap();
+    assert!(html.contains("<td><ul><li>10</li><li>20</li></ul></td>"));
+
+    let text = results.export(OutputFormat::Text).unwrap();
+    assert!(text.contains("Gi1   10, 20\n"));
+    assert!(!text.contains('['));
+
+    let options = ExportOptions::new().with_multiline_lists(true);
+    let text = results.export_with(OutputFormat::Text, &options).unwrap();
+    assert_eq!(text, "Name  Vlans\n----  -----\nGi1   10\n      20\nGi2\n");
+
+    let md = results.export(OutputFormat::Markdown).unwrap();
+    assert!(md.contains("| Gi1 | 10, 20 |\n| Gi2 |  |\n"));
+    let md = results
+        .export_with(OutputFormat::Markdown, &options)
+        .unwrap();
+    assert!(md.contains("| Gi1 | 10<br>20 |\n"));
+    let adoc = results
+        .export_with(OutputFormat::AsciiDoc, &options)
+        .unwrap();
+    assert!(adoc.contains("| Gi1 | 10 +\n20\n| Gi2 |\n"));
+}
+
+#[test]
+#[cfg(feature = "csv_export")]
+fn test_export_csv_list_separator() {
+    let results = get_list_results();
+    let csv = results.export(OutputFormat::Csv).unwrap();
+    assert!(csv.contains("Gi1,\"10, 20\"\n"));
+
+    let options = ExportOptions::new().with_list_separator(";");
+    let csv = results.export_with(OutputFormat::Csv, &options).unwrap();
+    assert!(csv.contains("Gi1,10;20\n"));
+    assert!(csv.contains("Gi2,\n"));
 }
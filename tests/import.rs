@@ -0,0 +1,176 @@
+use textfsm_rs::import::Importer;
+use textfsm_rs::{DataRecord, DataRecordConversion, OutputFormat, TextFSM, TextFsmExport};
+
+const TEMPLATE: &str = r"Value Key INTERFACE (\S+)
+Value STATUS (\S+)
+Value List VLANS (\d+)
+
+Start
+  ^Interface ${INTERFACE} is ${STATUS}
+  ^  vlan ${VLANS}
+  ^! -> Record
+";
+
+const INPUT: &str = "Interface Gi1 is up
+  vlan 10
+  vlan 20
+!
+Interface Gi2 is down
+!
+";
+
+fn parse(conversion: Option<DataRecordConversion>) -> (TextFSM, Vec<DataRecord>) {
+    let fsm = TextFSM::from_string(TEMPLATE).unwrap();
+    let records = fsm.clone().parse_string(INPUT, conversion).unwrap();
+    (fsm, records)
+}
+
+fn formats() -> Vec<OutputFormat> {
+    vec![
+        #[cfg(feature = "json")]
+        OutputFormat::Json,
+        #[cfg(feature = "json")]
+        OutputFormat::JsonLines,
+        #[cfg(feature = "yaml")]
+        OutputFormat::Yaml,
+        #[cfg(feature = "csv_export")]
+        OutputFormat::Csv,
+    ]
+}
+
+#[test]
+fn test_round_trip() {
+    let (fsm, records) = parse(None);
+    assert!(records[0].record_key.is_some());
+    for format in formats() {
+        let exported = records.export(format).unwrap();
+        let imported = Importer::new(format)
+            .with_template(&fsm, None)
+            .import_str(&exported)
+            .unwrap();
+        assert_eq!(imported, records, "{:?}", format);
+    }
+
+    // Field names follow the conversion
+    let (fsm, records) = parse(Some(DataRecordConversion::LowercaseKeys));
+    for format in formats() {
+        let exported = records.export(format).unwrap();
+        let imported = Importer::new(format)
+            .with_template(&fsm, Some(DataRecordConversion::LowercaseKeys))
+            .import_from(exported.as_bytes())
+            .unwrap();
+        assert_eq!(imported, records, "{:?}", format);
+    }
+}
+
+#[test]
+#[cfg(feature = "csv_export")]
+fn test_csv_without_template() {
+    use textfsm_rs::{ExportOptions, Value};
+
+    let (fsm, records) = parse(None);
+    let options = ExportOptions::new().with_list_separator(";");
+    let csv = records.export_with(OutputFormat::Csv, &options).unwrap();
+    assert!(csv.contains("Gi1,up,10;20\n"));
+
+    // Without the template every cell is a single value
+    let imported = Importer::new(OutputFormat::Csv).import_str(&csv).unwrap();
+    assert_eq!(
+        imported[0].get("VLANS"),
+        Some(&Value::Single("10;20".into()))
+    );
+    assert_eq!(imported[0].record_key, None);
+
+    let imported = Importer::new(OutputFormat::Csv)
+        .with_options(options)
+        .with_template(&fsm, None)
+        .import_str(&csv)
+        .unwrap();
+    assert_eq!(imported, records);
+    assert_eq!(imported[1].get("VLANS"), Some(&Value::List(vec![])));
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_json_lines_with_metadata() {
+    use textfsm_rs::export::{RecordMetadata, RecordSink};
+
+    let (fsm, records) = parse(None);
+    let mut sink = RecordSink::new(vec![], OutputFormat::JsonLines).with_metadata(RecordMetadata {
+        template: Some("interfaces.textfsm".into()),
+        ..Default::default()
+    });
+    for rec in &records {
+        sink.write_record(rec).unwrap();
+    }
+    let out = String::from_utf8(sink.finish().unwrap()).unwrap();
+    let imported = Importer::new(OutputFormat::JsonLines)
+        .with_template(&fsm, None)
+        .import_str(&out)
+        .unwrap();
+    assert_eq!(imported, records);
+
+    let err = Importer::new(OutputFormat::JsonLines)
+        .import_str("{\"A\": \"1\"}\nnot json\n")
+        .unwrap_err();
+    assert!(err.to_string().contains("line 2"));
+}
+
+#[test]
+#[cfg(feature = "yaml")]
+fn test_golden_yaml_and_diff() {
+    use textfsm_rs::diff::{DiffOptions, diff_records};
+
+    let (fsm, records) = parse(Some(DataRecordConversion::LowercaseKeys));
+    let golden = "---\nparsed_sample:\n  - interface: \"Gi1\"\n    status: \"up\"\n    vlans:\n      - \"10\"\n  - interface: \"Gi2\"\n    status: \"up\"\n    vlans: []\n";
+    let golden = Importer::new(OutputFormat::Yaml)
+        .with_template(&fsm, Some(DataRecordConversion::LowercaseKeys))
+        .import_str(golden)
+        .unwrap();
+    assert_eq!(golden.len(), 2);
+    assert_eq!(
+        golden[0].record_key.as_ref().unwrap().to_string(),
+        "interface=Gi1"
+    );
+
+    // Keys restored from the template align the records
+    let diff = diff_records(&golden, &records, &DiffOptions::default());
+    assert_eq!(diff.changed().count(), 2);
+    assert_eq!(diff.added().count(), 0);
+}
+
+#[test]
+#[cfg(all(feature = "json", feature = "yaml"))]
+fn test_record_key_of_earlier_releases() {
+    let (fsm, records) = parse(None);
+    // Earlier releases wrote record_key on every record: the key as a string,
+    // or null without Key Values
+    let json = r#"[
+        {"INTERFACE": "Gi1", "STATUS": "up", "VLANS": ["10", "20"], "record_key": "Single(\"Gi1\")"},
+        {"INTERFACE": "Gi2", "STATUS": "down", "VLANS": [], "record_key": null}
+    ]"#;
+    let json_lines = "{\"INTERFACE\": \"Gi1\", \"STATUS\": \"up\", \"VLANS\": [\"10\", \"20\"], \"record_key\": \"Single(\\\"Gi1\\\")\"}\n\
+                      {\"INTERFACE\": \"Gi2\", \"STATUS\": \"down\", \"VLANS\": [], \"record_key\": null}\n";
+    let yaml = "- INTERFACE: Gi1\n  STATUS: up\n  VLANS: [\"10\", \"20\"]\n  record_key: Single(\"Gi1\")\n\
+                - INTERFACE: Gi2\n  STATUS: down\n  VLANS: []\n  record_key: null\n";
+    for (format, input) in [
+        (OutputFormat::Json, json),
+        (OutputFormat::JsonLines, json_lines),
+        (OutputFormat::Yaml, yaml),
+    ] {
+        let imported = Importer::new(format)
+            .with_template(&fsm, None)
+            .import_str(input)
+            .unwrap();
+        assert_eq!(imported, records, "{:?}", format);
+    }
+}
+
+#[test]
+fn test_unsupported_format() {
+    assert!(
+        Importer::new(OutputFormat::Html)
+            .import_str("<table></table>")
+            .is_err()
+    );
+}
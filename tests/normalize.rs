@@ -0,0 +1,92 @@
+#![cfg(feature = "normalize")]
+
+use textfsm_rs::normalize::{MacFormat, Normalizer};
+use textfsm_rs::{Pipeline, TextFSM, Value};
+
+const TEMPLATE: &str = r"Value Key INTF (\S+)
+Value MACADDR (\S+)
+Value SPEED (\S+)
+Value UPTIME (.+)
+
+Start
+  ^${INTF}\s+${MACADDR}\s+${SPEED}\s+up\s+${UPTIME}$$ -> Record
+";
+
+const INPUT: &str = "Gi0/1 aabb.ccdd.0001 1000Mb/s up 1 week, 2 days, 3 hours
+Te1/0/1 AA-BB-CC-DD-00-02 10Gbps up 2d03h
+";
+
+fn single(s: &str) -> Value {
+    Value::Single(s.to_string())
+}
+
+#[test]
+fn test_normalize_pipeline() {
+    let pipeline = Pipeline::new()
+        .rename_common()
+        .lowercase_keys()
+        .normalize_common("cisco_ios")
+        .normalize("speed", Normalizer::Rate)
+        .normalize("uptime", Normalizer::Duration);
+    let mut fsm = TextFSM::from_string(TEMPLATE)
+        .unwrap()
+        .with_pipeline(pipeline);
+    let records = fsm.parse_string(INPUT, None).unwrap();
+
+    assert_eq!(
+        records[0].get("interface"),
+        Some(&single("GigabitEthernet0/1"))
+    );
+    assert_eq!(
+        records[0].get("mac_address"),
+        Some(&single("aa:bb:cc:dd:00:01"))
+    );
+    assert_eq!(records[0].get("speed"), Some(&single("1000000000")));
+    assert_eq!(records[0].get("uptime"), Some(&single("788400")));
+    assert_eq!(
+        records[0].record_key.as_ref().unwrap().to_string(),
+        "interface=GigabitEthernet0/1"
+    );
+
+    assert_eq!(
+        records[1].get("interface"),
+        Some(&single("TenGigabitEthernet1/0/1"))
+    );
+    assert_eq!(records[1].get("speed"), Some(&single("10000000000")));
+    assert_eq!(records[1].get("uptime"), Some(&single("183600")));
+}
+
+#[test]
+fn test_normalize_list_values() {
+    let mut value = Value::List(vec!["aabb.ccdd.eeff".to_string(), "not-a-mac".to_string()]);
+    Normalizer::MacAddress(MacFormat::Dash).apply(&mut value);
+    assert_eq!(
+        value,
+        Value::List(vec![
+            "aa-bb-cc-dd-ee-ff".to_string(),
+            "not-a-mac".to_string()
+        ])
+    );
+}
+
+#[test]
+fn test_duration_overflow() {
+    use textfsm_rs::normalize::duration_seconds;
+
+    // Too long for a u64: not recognized rather than a panic
+    assert_eq!(duration_seconds("999999999999 years"), None);
+    assert_eq!(duration_seconds("99999999999999999:00"), None);
+    assert_eq!(duration_seconds("18446744073709551615s 1s"), None);
+    assert_eq!(duration_seconds("18446744073709551615s"), Some(u64::MAX));
+}
+
+#[test]
+fn test_rate_overflow() {
+    use textfsm_rs::normalize::rate_bps;
+
+    assert_eq!(rate_bps("a-99999999999999"), None);
+    assert_eq!(
+        rate_bps("a-18446744073709"),
+        Some(18_446_744_073_709_000_000)
+    );
+}
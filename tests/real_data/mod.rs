@@ -8,6 +8,9 @@ fn get_data_dir() -> String {
     "tests/basic_template/cli".to_string()
 }
 
+// The `<name>_template`/`<name>_example` pairs have no expected output, so
+// this is a smoke test that each template parses its example; samples with
+// expected `parsed_sample` YAML are checked with `regression::RegressionRunner`.
 #[test]
 fn test_individual_templates() {
     let data_dir = get_data_dir();
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use textfsm_rs::{DataRecord, RecordKey, TextFSM, Value};
+
+// The key Values are captured by different rules, and VRF is filled down.
+const TEMPLATE: &str = r"Value Filldown,Key VRF (\S+)
+Value Key PREFIX (\S+)
+Value Required NEXT_HOP (\S+)
+
+Start
+  ^VRF ${VRF}
+  ^${PREFIX}\s*$$
+  ^\s+via ${NEXT_HOP} -> Record
+";
+
+const INPUT: &str = "VRF red
+10.0.0.0/8
+  via 192.0.2.1
+10.1.0.0/16
+  via 192.0.2.2
+VRF blue/2
+10.0.0.0/8
+  via 198.51.100.1
+";
+
+fn parse() -> Vec<DataRecord> {
+    let mut fsm = TextFSM::from_string(TEMPLATE).unwrap();
+    fsm.parse_string(INPUT, None).unwrap()
+}
+
+fn key(pairs: &[(&str, &str)]) -> RecordKey {
+    RecordKey(
+        pairs
+            .iter()
+            .map(|(n, v)| (n.to_string(), Value::Single(v.to_string())))
+            .collect(),
+    )
+}
+
+#[test]
+fn test_record_key_built_per_record() {
+    let records = parse();
+    let keys: Vec<RecordKey> = records
+        .iter()
+        .map(|r| r.record_key.clone().unwrap())
+        .collect();
+    assert_eq!(
+        keys,
+        vec![
+            key(&[("VRF", "red"), ("PREFIX", "10.0.0.0/8")]),
+            key(&[("VRF", "red"), ("PREFIX", "10.1.0.0/16")]),
+            key(&[("VRF", "blue/2"), ("PREFIX", "10.0.0.0/8")]),
+        ]
+    );
+    assert_eq!(keys[2].to_string(), "VRF=blue/2, PREFIX=10.0.0.0/8");
+    assert_eq!(
+        keys[2].get("VRF"),
+        Some(&Value::Single("blue/2".to_string()))
+    );
+    assert!(keys[0] < keys[1]);
+
+    // Templates without Key values produce no key.
+    let mut fsm = TextFSM::from_string("Value A (\\S+)\n\nStart\n  ^${A} -> Record\n").unwrap();
+    let records = fsm.parse_string("x\n", None).unwrap();
+    assert_eq!(records[0].record_key, None);
+}
+
+#[test]
+fn test_record_key_index() {
+    let records = parse();
+    let by_key: HashMap<RecordKey, &DataRecord> = DataRecord::index_refs_by_key(&records);
+    let rec = by_key[&key(&[("VRF", "red"), ("PREFIX", "10.1.0.0/16")])];
+    assert_eq!(
+        rec.get("NEXT_HOP"),
+        Some(&Value::Single("192.0.2.2".to_string()))
+    );
+
+    let owned = DataRecord::index_by_key(records);
+    assert_eq!(owned.len(), 3);
+    assert!(owned.contains_key(&key(&[("VRF", "blue/2"), ("PREFIX", "10.0.0.0/8")])));
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_record_key_serialization() {
+    let records = parse();
+    let key = records[0].record_key.clone().unwrap();
+    assert_eq!(
+        serde_json::to_value(&key).unwrap(),
+        serde_json::json!({"VRF": "red", "PREFIX": "10.0.0.0/8"})
+    );
+
+    // Key order survives a round trip.
+    let text = serde_json::to_string(&key).unwrap();
+    let back: RecordKey = serde_json::from_str(&text).unwrap();
+    assert_eq!(back, key);
+
+    // The key repeats fields of the record, so records serialize without it.
+    let json = serde_json::to_value(&records[0]).unwrap();
+    assert!(json.get("record_key").is_none());
+    let back: DataRecord = serde_json::from_value(json).unwrap();
+    assert_eq!(back.fields, records[0].fields);
+    assert_eq!(back.record_key, None);
+
+    // Records without a key do not serialize one.
+    let mut rec = DataRecord::new();
+    rec.insert("A".to_string(), "x".to_string());
+    assert_eq!(serde_json::to_string(&rec).unwrap(), "{\"A\":\"x\"}");
+}
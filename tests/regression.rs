@@ -0,0 +1,135 @@
+#![cfg(feature = "yaml")]
+
+use std::fs;
+use std::path::Path;
+use textfsm_rs::diff::RecordChange;
+use textfsm_rs::regression::{CaseStatus, RegressionRunner};
+
+const TEMPLATE: &str = "Value Required NAME (\\S+)\nValue List PORTS (\\d+)\n\nStart\n  ^Name: -> Continue.Record\n  ^Name: ${NAME}\n  ^  port ${PORTS}\n";
+
+const RAW: &str = "Name: eth0\n  port 1\n  port 2\nName: eth1\n";
+
+fn write(path: &Path, content: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+fn sample_tree(root: &Path) {
+    write(&root.join("templates/os_show_names.textfsm"), TEMPLATE);
+    let cases = root.join("tests/os/show_names");
+    write(&cases.join("os_show_names.raw"), "Name: eth0\nName: eth1\n");
+    // Expected records in a different order still pass.
+    write(
+        &cases.join("os_show_names.yml"),
+        "---\nparsed_sample:\n  - name: \"eth1\"\n    ports: []\n  - name: \"eth0\"\n    ports: []\n",
+    );
+    write(&cases.join("os_show_names_ports.raw"), RAW);
+    write(
+        &cases.join("os_show_names_ports.yml"),
+        "---\nparsed_sample:\n  - name: \"eth0\"\n    ports:\n      - \"1\"\n  - name: \"eth1\"\n    ports: []\n",
+    );
+    // No expected output yet.
+    write(&cases.join("os_show_names_new.raw"), RAW);
+    // No template for this command.
+    write(&root.join("tests/os/show_other/os_show_other.raw"), RAW);
+}
+
+#[test]
+fn test_regression_run() {
+    let dir = tempfile::tempdir().unwrap();
+    sample_tree(dir.path());
+    let runner = RegressionRunner::new(dir.path().join("templates"), dir.path().join("tests"));
+
+    let report = runner.run().unwrap();
+    let ids: Vec<String> = report.results.iter().map(|r| r.case.id()).collect();
+    assert_eq!(
+        ids,
+        vec![
+            "os/show_names/os_show_names",
+            "os/show_names/os_show_names_new",
+            "os/show_names/os_show_names_ports",
+            "os/show_other/os_show_other",
+        ]
+    );
+    assert_eq!(
+        (report.passed(), report.failed(), report.errors()),
+        (1, 1, 2)
+    );
+
+    match &report.results[2].status {
+        CaseStatus::Failed(diff) => {
+            assert_eq!(diff.changes.len(), 1);
+            let RecordChange::Changed { fields, .. } = &diff.changes[0] else {
+                panic!("expected a changed record, got {:?}", diff);
+            };
+            assert_eq!(fields[0].field, "ports");
+            assert!(
+                report.results[2]
+                    .to_string()
+                    .contains("ports: [\"1\"] -> [\"1\", \"2\"]"),
+                "{}",
+                report.results[2]
+            );
+        }
+        x => panic!("expected a failure, got {:?}", x),
+    }
+
+    let junit = report.to_junit();
+    assert!(junit.contains("<testsuites name=\"textfsm\" tests=\"4\" failures=\"1\" errors=\"2\""));
+    assert!(junit.contains("<testcase classname=\"os.show_names\" name=\"os_show_names\""));
+    assert!(junit.contains("<failure message=\"1 record difference(s)\">"));
+
+    // Ordered comparison fails the reordered case too.
+    let mut ordered = runner.clone();
+    ordered.ordered = true;
+    ordered.filter = Some("os_show_names".to_string());
+    let report = ordered.run().unwrap();
+    assert_eq!(report.results.len(), 3);
+    assert_eq!(report.passed(), 0);
+}
+
+#[test]
+fn test_junit_control_characters() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(
+        &root.join("templates/os_show_names.textfsm"),
+        &TEMPLATE.replace("Required NAME", "Key NAME"),
+    );
+    let cases = root.join("tests/os/show_names");
+    // Raw device output with an escape sequence ends up in the record key of
+    // the diff
+    write(&cases.join("os_show_names.raw"), "Name: \x1b[1meth0\n");
+    write(
+        &cases.join("os_show_names.yml"),
+        "---\nparsed_sample:\n  - name: \"\\x1b[1meth0\"\n    ports: [\"1\"]\n",
+    );
+    let runner = RegressionRunner::new(root.join("templates"), root.join("tests"));
+
+    let report = runner.run().unwrap();
+    assert_eq!(report.failed(), 1);
+    let junit = report.to_junit();
+    assert!(!junit.contains('\x1b'), "{}", junit);
+    assert!(junit.contains("name=\u{fffd}[1meth0"), "{}", junit);
+}
+
+#[test]
+fn test_regression_bless() {
+    let dir = tempfile::tempdir().unwrap();
+    sample_tree(dir.path());
+    let runner = RegressionRunner::new(dir.path().join("templates"), dir.path().join("tests"));
+
+    let report = runner.run().unwrap();
+    let blessed: Vec<bool> = report.results.iter().map(|r| r.bless().unwrap()).collect();
+    assert_eq!(blessed, vec![false, true, true, false]);
+
+    let expected =
+        fs::read_to_string(dir.path().join("tests/os/show_names/os_show_names_new.yml")).unwrap();
+    assert_eq!(
+        expected,
+        "---\nparsed_sample:\n  - name: \"eth0\"\n    ports:\n      - \"1\"\n      - \"2\"\n  - name: \"eth1\"\n    ports: []\n"
+    );
+
+    let report = runner.run().unwrap();
+    assert_eq!((report.passed(), report.errors()), (3, 1));
+}
@@ -0,0 +1,104 @@
+use textfsm_rs::export::RecordMetadata;
+use textfsm_rs::report::{HtmlReport, ReportSection};
+use textfsm_rs::{ResultTable, TextFSM};
+
+const TEMPLATE: &str = r"Value INTERFACE (\S+)
+Value List VLANS (\d+)
+
+Start
+  ^interface ${INTERFACE} -> Continue
+  ^interface -> Interface
+
+Interface
+  ^  vlan ${VLANS}
+  ^! -> Record Start
+";
+
+const INPUT: &str = "Building configuration...
+
+interface Gi1
+  vlan 10
+  vlan 20
+  description <uplink>
+!
+interface Gi2
+!
+end
+";
+
+#[test]
+fn test_unmatched_lines() {
+    // Not collected unless asked for
+    let mut fsm = TextFSM::from_string(TEMPLATE).unwrap();
+    fsm.parse_string(INPUT, None).unwrap();
+    assert!(fsm.unmatched_lines.is_empty());
+
+    // Blank lines are skipped, and lines matched before a state change count
+    // as matched
+    let mut fsm = TextFSM::from_string(TEMPLATE)
+        .unwrap()
+        .with_unmatched_lines();
+    fsm.parse_string(INPUT, None).unwrap();
+    assert_eq!(
+        fsm.unmatched_lines,
+        vec![
+            (1, "Building configuration...".to_string()),
+            (6, "  description <uplink>".to_string()),
+            (10, "end".to_string()),
+        ]
+    );
+
+    fsm.reset();
+    assert!(fsm.unmatched_lines.is_empty());
+
+    let fsm = TextFSM::from_string(TEMPLATE)
+        .unwrap()
+        .with_unmatched_lines();
+    let mut iter = fsm.parse_reader(INPUT.as_bytes());
+    iter.next().unwrap().unwrap();
+    assert_eq!(iter.unmatched_lines().len(), 2);
+    assert_eq!(iter.count(), 1);
+}
+
+#[test]
+fn test_html_report() {
+    let mut fsm = TextFSM::from_string(TEMPLATE)
+        .unwrap()
+        .with_unmatched_lines();
+    let section = ReportSection::parse(&mut fsm, INPUT, None)
+        .unwrap()
+        .with_metadata(RecordMetadata {
+            template: Some("vlans.textfsm".into()),
+            platform: Some("cisco_ios".into()),
+            command: Some("show running-config".into()),
+            source: None,
+        });
+    assert_eq!(section.header, vec!["INTERFACE", "VLANS"]);
+    assert_eq!(section.records.len(), 2);
+
+    let empty = ReportSection::from(ResultTable::new(vec!["NAME".into()], vec![]))
+        .with_title("show <nothing>");
+    let report = HtmlReport::new("Router & switch")
+        .with_section(section)
+        .with_section(empty);
+    let html = report.render().unwrap();
+
+    assert!(html.starts_with("<!DOCTYPE html>\n"));
+    assert!(html.ends_with("</html>\n"));
+    assert!(html.contains("<title>Router &amp; switch</title>"));
+    // Everything is inline
+    assert!(html.contains("<style>") && html.contains("<script>"));
+    assert!(!html.contains("src=") && !html.contains("href=\"http"));
+
+    assert!(html.contains("<a href=\"#section-1\">show running-config</a> (2 records)"));
+    assert!(html.contains("<dt>Platform</dt><dd>cisco_ios</dd>"));
+    assert!(!html.contains("<dt>Source</dt>"));
+    assert!(html.contains("<summary>Warning: 3 input lines not matched by the template</summary>"));
+    assert!(html.contains("    6:   description &lt;uplink&gt;\n"));
+    assert!(html.contains("<tr><th>INTERFACE</th><th>VLANS</th></tr>"));
+    // Lists are rendered as in the HTML export
+    assert!(html.contains("<tr><td>Gi1</td><td><ul><li>10</li><li>20</li></ul></td></tr>"));
+    assert!(html.contains("<tr><td>Gi2</td><td></td></tr>"));
+
+    assert!(html.contains("<h2>show &lt;nothing&gt;</h2>\n<p class=\"empty\">No records.</p>"));
+}
@@ -0,0 +1,162 @@
+use textfsm_rs::{DataRecordConversion, ResultTable, TextFSM, Value};
+
+const INTERFACES: &str = r"Value Key INTERFACE (\S+)
+Value LINK_STATUS (up|down)
+Value MTU (\d+)
+
+Start
+  ^${INTERFACE} is ${LINK_STATUS}, mtu ${MTU} -> Record
+";
+
+const INTERFACES_INPUT: &str = "Gi0/10 is up, mtu 9000
+Gi0/2 is down, mtu 1500
+Gi0/1 is up, mtu 1500
+";
+
+const BRIEF: &str = r"Value INTERFACE (\S+)
+Value IP_ADDRESS (\S+)
+Value LINK_STATUS (\S+)
+
+Start
+  ^${INTERFACE}\s+${IP_ADDRESS}\s+${LINK_STATUS} -> Record
+";
+
+const BRIEF_INPUT: &str = "Gi0/1 192.0.2.1 up
+Gi0/10 unassigned admin-down
+Lo0 198.51.100.1 up
+";
+
+fn single(s: &str) -> Value {
+    Value::Single(s.to_string())
+}
+
+fn tables() -> (ResultTable, ResultTable) {
+    let mut fsm = TextFSM::from_string(INTERFACES).unwrap();
+    let interfaces = fsm.parse_string_table(INTERFACES_INPUT, None).unwrap();
+    let mut fsm = TextFSM::from_string(BRIEF).unwrap();
+    let brief = fsm.parse_string_table(BRIEF_INPUT, None).unwrap();
+    (interfaces, brief)
+}
+
+#[test]
+fn test_result_table_queries() {
+    let (mut interfaces, _) = tables();
+    assert_eq!(interfaces.header, vec!["INTERFACE", "LINK_STATUS", "MTU"]);
+    assert_eq!(interfaces.len(), 3);
+
+    let rec = interfaces.get_by_key_values(&["Gi0/2"]).unwrap();
+    assert_eq!(rec.get("LINK_STATUS"), Some(&single("down")));
+    let key = rec.record_key.clone().unwrap();
+    assert_eq!(interfaces.get_by_key(&key), Some(rec));
+
+    let up = interfaces.filter("LINK_STATUS", |v| *v == single("up"));
+    assert_eq!(
+        up.column("INTERFACE"),
+        vec![&single("Gi0/10"), &single("Gi0/1")]
+    );
+
+    let groups = interfaces.group_by("MTU");
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[&single("1500")].len(), 2);
+
+    let selected = interfaces.select(&["MTU", "INTERFACE"]);
+    assert_eq!(selected.header, vec!["MTU", "INTERFACE"]);
+    assert!(selected.iter().all(|r| r.get("LINK_STATUS").is_none()));
+
+    interfaces.sort_by(&["MTU", "INTERFACE"]);
+    assert_eq!(
+        interfaces.column("INTERFACE"),
+        vec![&single("Gi0/1"), &single("Gi0/2"), &single("Gi0/10")]
+    );
+}
+
+#[test]
+fn test_result_table_join() {
+    let (interfaces, brief) = tables();
+
+    let joined = interfaces.join(&brief, "INTERFACE");
+    assert_eq!(
+        joined.header,
+        vec!["INTERFACE", "LINK_STATUS", "MTU", "IP_ADDRESS"]
+    );
+    assert_eq!(
+        joined.column("INTERFACE"),
+        vec![&single("Gi0/10"), &single("Gi0/1")]
+    );
+    // The left table wins on conflicting fields.
+    assert_eq!(joined.records[0].get("LINK_STATUS"), Some(&single("up")));
+    assert_eq!(
+        joined.records[0].get("IP_ADDRESS"),
+        Some(&single("unassigned"))
+    );
+
+    let left = interfaces.left_join(&brief, "INTERFACE");
+    assert_eq!(left.len(), 3);
+    assert_eq!(left.records[1].get("IP_ADDRESS"), None);
+}
+
+#[test]
+fn test_result_table_lowercase_header() {
+    let mut fsm = TextFSM::from_string(INTERFACES).unwrap();
+    let table = fsm
+        .parse_string_table(INTERFACES_INPUT, Some(DataRecordConversion::LowercaseKeys))
+        .unwrap();
+    assert_eq!(table.header, vec!["interface", "link_status", "mtu"]);
+    assert!(table.get_by_key_values(&["Gi0/1"]).is_some());
+    assert_eq!(Vec::from(table).len(), 3);
+}
+
+#[test]
+fn test_result_table_sort_mixed_values() {
+    use textfsm_rs::DataRecord;
+
+    let values = [
+        "1a", "10", "nan", "2", "inf", "-1.5", "", "abc", "2.0", "1a",
+    ];
+    let records = values
+        .iter()
+        .map(|v| {
+            let mut rec = DataRecord::new();
+            rec.insert("V".into(), v.to_string());
+            rec
+        })
+        .collect();
+    let mut table = ResultTable::new(vec!["V".into()], records);
+    table.sort_by(&["V"]);
+    // Numbers come first, in numeric order; everything else, including NaN
+    // and inf, sorts as text
+    let sorted: Vec<String> = table.column("V").iter().map(|v| v.to_string()).collect();
+    assert_eq!(
+        sorted,
+        vec![
+            "-1.5", "2", "2.0", "10", "", "1a", "1a", "abc", "inf", "nan"
+        ]
+    );
+
+    for a in &values {
+        for b in &values {
+            let (a, b) = (single(a), single(b));
+            assert_eq!(
+                textfsm_rs::table::compare_values(&a, &b),
+                textfsm_rs::table::compare_values(&b, &a).reverse()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_result_table_export_keeps_header_order() {
+    use textfsm_rs::{OutputFormat, TextFsmExport};
+
+    let (interfaces, _) = tables();
+    let table = interfaces.select(&["MTU", "INTERFACE"]);
+    let text = table.export(OutputFormat::Text).unwrap();
+    assert!(text.starts_with("MTU   INTERFACE\n"), "{}", text);
+    #[cfg(feature = "csv_export")]
+    assert!(
+        table
+            .export(OutputFormat::Csv)
+            .unwrap()
+            .starts_with("MTU,INTERFACE\n")
+    );
+}
@@ -0,0 +1,91 @@
+use textfsm_rs::schema::{Schema, SchemaIssueKind, ValueType};
+use textfsm_rs::{TextFSM, TextFsmError};
+
+const TEMPLATE: &str = r"Value INTF (\S+)
+Value IP_ADDRESSES (\S+)
+Value List MEMBER_INTERFACE (\S+)
+Value VLAN_ID (\S+)
+
+Start
+  ^${INTF}\s+${IP_ADDRESSES}\s+${VLAN_ID} -> Record
+";
+
+#[test]
+fn test_schema_lint() {
+    let fsm = TextFSM::from_string(TEMPLATE).unwrap();
+    let issues = Schema::common().lint(&fsm.parser);
+    let found: Vec<(&str, SchemaIssueKind)> =
+        issues.iter().map(|i| (i.value.as_str(), i.kind)).collect();
+    assert_eq!(
+        found,
+        vec![
+            ("INTF", SchemaIssueKind::Synonym),
+            ("IP_ADDRESSES", SchemaIssueKind::Cardinality),
+        ]
+    );
+    assert_eq!(
+        issues[0].to_string(),
+        "INTF: Synonym: use INTERFACE instead of INTF"
+    );
+}
+
+#[test]
+fn test_schema_lint_prefix_length() {
+    let template = "Value NETWORK (\\S+)\nValue PREFIX (\\d+)\nValue CIDR (\\d+)\n\nStart\n  ^${NETWORK}/${PREFIX} ${CIDR} -> Record\n";
+    let fsm = TextFSM::from_string(template).unwrap();
+    let issues: Vec<String> = Schema::common()
+        .lint(&fsm.parser)
+        .iter()
+        .map(|i| i.to_string())
+        .collect();
+    assert_eq!(
+        issues,
+        vec![
+            "PREFIX: Synonym: use PREFIX_LENGTH instead of PREFIX",
+            "CIDR: Synonym: use PREFIX_LENGTH instead of CIDR",
+        ]
+    );
+}
+
+#[test]
+fn test_schema_check_at_parse_time() {
+    let mut fsm = TextFSM::from_string(TEMPLATE)
+        .unwrap()
+        .with_schema(Schema::common());
+    let records = fsm.parse_string("Gi0/1 192.0.2.1 10\n", None).unwrap();
+    assert_eq!(records.len(), 1);
+
+    let mut fsm = TextFSM::from_string(TEMPLATE)
+        .unwrap()
+        .with_schema(Schema::common());
+    match fsm.parse_string("Gi0/1 192.0.2.1 5000\n", None) {
+        Err(TextFsmError::ParseError(msg)) => {
+            assert_eq!(msg, "value VLAN_ID '5000' is not a valid VlanId")
+        }
+        x => panic!("expected a type error, got {:?}", x),
+    }
+
+    // Without a schema nothing is checked.
+    let mut fsm = TextFSM::from_string(TEMPLATE).unwrap();
+    assert!(fsm.parse_string("Gi0/1 unassigned 5000\n", None).is_ok());
+
+    let fsm = TextFSM::from_string(TEMPLATE)
+        .unwrap()
+        .with_schema(Schema::common());
+    let streamed: Vec<_> = fsm
+        .parse_reader(std::io::Cursor::new("Gi0/1 unassigned 10\n"))
+        .collect();
+    assert!(matches!(streamed[..], [Err(TextFsmError::ParseError(_))]));
+}
+
+#[test]
+fn test_value_types() {
+    assert!(ValueType::MacAddress.matches("aabb.ccdd.eeff"));
+    assert!(!ValueType::MacAddress.matches("aabb.ccdd"));
+    assert!(ValueType::Netmask.matches("255.255.255.0"));
+    assert!(!ValueType::Netmask.matches("255.0.255.0"));
+    assert!(ValueType::IpAddress.matches("2001:db8::1"));
+    assert!(!ValueType::Ipv4Address.matches("2001:db8::1"));
+    assert!(ValueType::PrefixLength.matches("64"));
+    assert!(ValueType::VlanId.matches(""));
+}
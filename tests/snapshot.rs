@@ -0,0 +1,63 @@
+use textfsm_rs::TextFSM;
+use textfsm_rs::Value;
+use textfsm_rs::snapshot::{ChangeDetector, ChangeEvent};
+
+const TEMPLATE: &str = "Value Key INTERFACE (\\S+)\nValue Key VRF (\\S+)\nValue STATUS (\\S+)\n\nStart\n  ^${INTERFACE}\\s+${VRF}\\s+${STATUS} -> Record\n";
+
+#[test]
+fn test_change_events() {
+    let mut fsm = TextFSM::from_string(TEMPLATE).unwrap();
+    let detector = ChangeDetector::from_parser(&fsm.parser);
+    assert_eq!(detector.key_fields, vec!["INTERFACE", "VRF"]);
+
+    let old = fsm
+        .parse_string("Gi1 default up\nGi2 default up\nGi2 mgmt up\n", None)
+        .unwrap();
+    let mut fsm = TextFSM::from_string(TEMPLATE).unwrap();
+    let new = fsm
+        .parse_string("Gi2 mgmt down\nGi1 default up\nGi3 default up\n", None)
+        .unwrap();
+
+    let events = detector.detect(&old, &new);
+    assert_eq!(events.len(), 3, "{:#?}", events);
+    match &events[0] {
+        ChangeEvent::FieldChanged {
+            key,
+            field,
+            old,
+            new,
+        } => {
+            assert_eq!(key.to_string(), "INTERFACE=Gi2, VRF=mgmt");
+            assert_eq!(field, "STATUS");
+            assert_eq!(old, &Some(Value::Single("up".into())));
+            assert_eq!(new, &Some(Value::Single("down".into())));
+        }
+        x => panic!("expected a field change, got {:?}", x),
+    }
+    assert!(
+        matches!(&events[1], ChangeEvent::Disappeared { key, .. } if key.to_string() == "INTERFACE=Gi2, VRF=default")
+    );
+    assert!(
+        matches!(&events[2], ChangeEvent::Appeared { key, .. } if key.get("INTERFACE") == Some(&Value::Single("Gi3".into())))
+    );
+
+    // Nothing changed between identical snapshots.
+    assert!(detector.detect(&new, &new).is_empty());
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_change_events_json_lines() {
+    use textfsm_rs::snapshot::to_json_lines;
+    let mut fsm = TextFSM::from_string(TEMPLATE).unwrap();
+    let old = fsm.parse_string("Gi1 default up\n", None).unwrap();
+    let mut fsm = TextFSM::from_string(TEMPLATE).unwrap();
+    let new = fsm.parse_string("Gi1 default down\n", None).unwrap();
+
+    let lines =
+        to_json_lines(&ChangeDetector::from_parser(&fsm.parser).detect(&old, &new)).unwrap();
+    assert_eq!(
+        lines,
+        "{\"event\":\"field_changed\",\"key\":{\"INTERFACE\":\"Gi1\",\"VRF\":\"default\"},\"field\":\"STATUS\",\"old\":\"up\",\"new\":\"down\"}\n"
+    );
+}
@@ -0,0 +1,124 @@
+use textfsm_rs::TextFSM;
+use textfsm_rs::source::{FileSource, MemorySource, TemplateSource};
+
+const INDEX: &str = "Template, Vendor, Command\n\
+                     names, VendorA, sh[[ow]] na[[mes]]\n";
+const NAMES_TEMPLATE: &str = "Value Name (\\S+)\n\nStart\n  ^Name: ${Name} -> Record\n";
+
+fn memory_source() -> MemorySource {
+    let mut source = MemorySource::new("test");
+    source
+        .insert("index", INDEX)
+        .insert("names", NAMES_TEMPLATE);
+    source
+}
+
+#[test]
+fn test_memory_source() {
+    let source = memory_source();
+    assert_eq!(source.location(), "memory:test");
+    assert_eq!(source.list().unwrap(), vec!["index", "names"]);
+    assert_eq!(source.read_index().unwrap(), INDEX);
+    assert!(source.read_template("missing").is_err());
+
+    let mut fsm = TextFSM::from_source(&source, "names").unwrap();
+    let result = fsm.parse_string("Name: Alice\nName: Bob\n", None).unwrap();
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[1].get("Name").unwrap().to_string(), "Bob");
+}
+
+#[test]
+fn test_file_source() {
+    let source = FileSource::from_index("tests/basic_template/site/index");
+    assert_eq!(source.location(), "tests/basic_template/site");
+    assert_eq!(
+        source.list().unwrap(),
+        vec!["index", "site_templateA", "site_templateE"]
+    );
+    assert!(TextFSM::from_source(&source, "site_templateE").is_ok());
+
+    let err = TextFSM::from_source(&memory_source(), "index").unwrap_err();
+    assert!(err.to_string().contains("template index in memory:test"));
+}
+
+#[test]
+#[cfg(feature = "clitable")]
+fn test_cli_table_from_memory_source() {
+    use textfsm_rs::CliTable;
+    let cli_table = CliTable::from_source(memory_source()).unwrap();
+    let (dir, row) = cli_table
+        .get_template_for_command("VendorA", "sh na")
+        .unwrap();
+    assert_eq!(dir, "memory:test");
+    assert_eq!(row.templates, vec!["names"]);
+
+    let parsers = cli_table
+        .get_compiled_templates_for_command("VendorA", "show names")
+        .unwrap()
+        .unwrap();
+    let mut fsm = TextFSM::from_shared(parsers[0].clone());
+    let result = fsm.parse_string("Name: Carol\n", None).unwrap();
+    assert_eq!(result[0].get("Name").unwrap().to_string(), "Carol");
+
+    let mut broken = memory_source();
+    broken.files.remove("names");
+    let cli_table = CliTable::from_source(broken).unwrap();
+    assert_eq!(cli_table.precompile_templates().len(), 1);
+}
+
+#[test]
+#[cfg(all(feature = "tar", feature = "clitable"))]
+fn test_tar_source() {
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use textfsm_rs::CliTable;
+
+    let mut builder = tar::Builder::new(GzEncoder::new(vec![], Compression::default()));
+    for (name, content) in [
+        ("bundle-1.0/README", "not a template\n"),
+        ("bundle-1.0/templates/index", INDEX),
+        ("bundle-1.0/templates/names", NAMES_TEMPLATE),
+    ] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, name, content.as_bytes())
+            .unwrap();
+    }
+    let data = builder.into_inner().unwrap().finish().unwrap();
+
+    let source = MemorySource::from_tar("bundle.tar.gz", data.as_slice()).unwrap();
+    // Everything outside the directory holding the index is dropped.
+    assert_eq!(source.list().unwrap(), vec!["index", "names"]);
+
+    let cli_table = CliTable::from_source(source).unwrap();
+    assert!(cli_table.precompile_templates().is_empty());
+}
+
+#[test]
+#[cfg(all(feature = "zip", feature = "clitable"))]
+fn test_zip_source() {
+    use std::io::Write;
+    use textfsm_rs::CliTable;
+
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(vec![]));
+    let options = zip::write::SimpleFileOptions::default();
+    for (name, content) in [("index", INDEX), ("names", NAMES_TEMPLATE)] {
+        writer.start_file(name, options).unwrap();
+        writer.write_all(content.as_bytes()).unwrap();
+    }
+    let data = writer.finish().unwrap().into_inner();
+
+    let source = MemorySource::from_zip("bundle.zip", std::io::Cursor::new(data)).unwrap();
+    assert_eq!(source.list().unwrap(), vec!["index", "names"]);
+
+    let cli_table = CliTable::from_source(source).unwrap();
+    assert!(
+        cli_table
+            .get_template_for_command("VendorA", "show names")
+            .is_some()
+    );
+    assert!(cli_table.precompile_templates().is_empty());
+}
@@ -0,0 +1,152 @@
+#![cfg(feature = "sqlite")]
+
+use rusqlite::Connection;
+use textfsm_rs::sqlite::{SqliteExport, SqliteWriter, table_name};
+use textfsm_rs::{DataRecordConversion, Pipeline, TextFSM, Value};
+
+const TEMPLATE: &str = r"Value Key INTERFACE (\S+)
+Value STATUS (\S+)
+Value List VLANS (\d+)
+
+Start
+  ^Interface ${INTERFACE} is ${STATUS}
+  ^  vlan ${VLANS}
+  ^! -> Record
+";
+
+const INPUT: &str = "Interface Gi1 is up
+  vlan 10
+  vlan 20
+!
+Interface Gi2 is down
+!
+";
+
+fn rows(conn: &Connection, sql: &str) -> Vec<(String, String, Option<String>)> {
+    conn.prepare(sql)
+        .unwrap()
+        .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect()
+}
+
+#[test]
+fn test_table_name() {
+    assert_eq!(
+        table_name("templates/cisco_ios_show_ip_route.textfsm"),
+        "cisco_ios_show_ip_route"
+    );
+    assert_eq!(
+        table_name("cisco_ios show ip route"),
+        "cisco_ios_show_ip_route"
+    );
+    assert_eq!(table_name("3com show vlan"), "t_3com_show_vlan");
+}
+
+#[test]
+fn test_writer_upserts_by_key() {
+    let conn = Connection::open_in_memory().unwrap();
+    let fsm = TextFSM::from_string(TEMPLATE).unwrap();
+
+    let mut writer = SqliteWriter::new(&conn, "interfaces", &fsm).unwrap();
+    for record in fsm.clone().parse_reader(INPUT.as_bytes()) {
+        writer.write_record(&record.unwrap()).unwrap();
+    }
+    assert_eq!(writer.count(), 2);
+
+    let sql = "SELECT INTERFACE, STATUS, VLANS FROM interfaces ORDER BY INTERFACE";
+    assert_eq!(
+        rows(&conn, sql),
+        vec![
+            ("Gi1".into(), "up".into(), Some(r#"["10","20"]"#.into())),
+            ("Gi2".into(), "down".into(), Some("[]".into())),
+        ]
+    );
+
+    // Parsing the command again updates the rows in place
+    let mut fsm = TextFSM::from_string(TEMPLATE).unwrap();
+    let records = fsm
+        .parse_string("Interface Gi2 is up\n!\nInterface Gi3 is up\n!\n", None)
+        .unwrap();
+    let mut writer = SqliteWriter::new(&conn, "interfaces", &fsm).unwrap();
+    writer.write_all(&records).unwrap();
+    assert_eq!(
+        rows(&conn, sql),
+        vec![
+            ("Gi1".into(), "up".into(), Some(r#"["10","20"]"#.into())),
+            ("Gi2".into(), "up".into(), Some("[]".into())),
+            ("Gi3".into(), "up".into(), Some("[]".into())),
+        ]
+    );
+}
+
+#[test]
+fn test_export_sqlite() {
+    let conn = Connection::open_in_memory().unwrap();
+    let mut fsm = TextFSM::from_string(TEMPLATE).unwrap();
+    let records = fsm
+        .parse_string(INPUT, Some(DataRecordConversion::LowercaseKeys))
+        .unwrap();
+    assert_eq!(records.export_sqlite(&conn, "interfaces").unwrap(), 2);
+    assert_eq!(records.export_sqlite(&conn, "interfaces").unwrap(), 2);
+    let sql = "SELECT interface, status, vlans FROM interfaces ORDER BY interface";
+    assert_eq!(rows(&conn, sql).len(), 2);
+
+    // A template gaining a Value adds a column to the existing table
+    let mut fsm = TextFSM::from_string(TEMPLATE).unwrap().with_pipeline(
+        Pipeline::new().lowercase_keys().map(|r| {
+            r.insert("speed".into(), "1000".into());
+        }),
+    );
+    let table = fsm.parse_string_table(INPUT, None).unwrap();
+    let mut header = table.header.clone();
+    header.push("speed".into());
+    let table = textfsm_rs::ResultTable::new(header, table.records);
+    assert_eq!(table.export_sqlite(&conn, "interfaces").unwrap(), 2);
+    let speeds: Vec<String> = conn
+        .prepare("SELECT speed FROM interfaces")
+        .unwrap()
+        .query_map([], |r| r.get(0))
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(speeds, vec!["1000", "1000"]);
+}
+
+#[test]
+fn test_writer_keys_existing_table() {
+    let conn = Connection::open_in_memory().unwrap();
+    // Created without a primary key, e.g. by a template without Key Values
+    conn.execute(
+        "CREATE TABLE interfaces (INTERFACE TEXT, STATUS TEXT, VLANS TEXT)",
+        [],
+    )
+    .unwrap();
+    let mut fsm = TextFSM::from_string(TEMPLATE).unwrap();
+    let records = fsm.parse_string(INPUT, None).unwrap();
+    let mut writer = SqliteWriter::new(&conn, "interfaces", &fsm).unwrap();
+    writer.write_all(&records).unwrap();
+    writer.write_all(&records).unwrap();
+    let sql = "SELECT INTERFACE, STATUS, VLANS FROM interfaces ORDER BY INTERFACE";
+    assert_eq!(rows(&conn, sql).len(), 2);
+
+    // A record without its key value is stored under '' and upserted too
+    let mut rec = textfsm_rs::DataRecord::new();
+    rec.insert("STATUS".into(), "up".into());
+    writer.write_record(&rec).unwrap();
+    rec.fields
+        .insert("STATUS".into(), Value::Single("down".into()));
+    writer.write_record(&rec).unwrap();
+    let rows = rows(&conn, sql);
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0], ("".into(), "down".into(), None));
+
+    // New tables do not take NULL keys
+    let conn = Connection::open_in_memory().unwrap();
+    SqliteWriter::new(&conn, "interfaces", &fsm).unwrap();
+    assert!(
+        conn.execute("INSERT INTO interfaces (INTERFACE) VALUES (NULL)", [])
+            .is_err()
+    );
+}
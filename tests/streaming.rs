@@ -1,5 +1,5 @@
 use std::io::Cursor;
-use textfsm_rs::TextFSM;
+use textfsm_rs::{LineRange, TextFSM};
 
 #[test]
 fn test_parse_reader_streaming() {
@@ -65,3 +65,36 @@ Start
 
     assert!(iter.next().is_none());
 }
+
+#[test]
+fn test_record_line_ranges() {
+    let template = r###"Value Required Name (\S+)
+Value Age (\d+)
+
+Start
+  ^Name: ${Name}
+  ^Age: ${Age} -> Record
+"###;
+
+    let data = "header\nName: Alice\nnoise\nAge: 30\nAge: 99\nName: Bob\n";
+    // The second Age is not recorded without a Name, so it stays in Bob's record
+    let expected = vec![
+        LineRange { start: 2, end: 4 },
+        LineRange { start: 5, end: 6 },
+    ];
+
+    let mut fsm = TextFSM::from_string(template).unwrap();
+    let records = fsm.parse_string(data, None).unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1].get("Age").unwrap().to_string(), "99");
+    assert_eq!(fsm.parsed_lines(), expected);
+
+    let fsm = TextFSM::from_string(template).unwrap();
+    let mut iter = fsm.parse_reader(Cursor::new(data));
+    let mut lines = vec![];
+    while let Some(record) = iter.next() {
+        record.unwrap();
+        lines.push(iter.last_lines().unwrap());
+    }
+    assert_eq!(lines, expected);
+}
@@ -0,0 +1,138 @@
+use textfsm_rs::terminal::{BorderStyle, Layout, Overflow, display_width, render};
+use textfsm_rs::{ExportOptions, OutputFormat, TableStyle, TextFSM, TextFsmExport};
+
+fn cells(row: &[&str]) -> Vec<Vec<String>> {
+    row.iter()
+        .map(|c| c.lines().map(String::from).collect())
+        .collect()
+}
+
+fn headers() -> Vec<String> {
+    vec!["NAME".to_string(), "CITY".to_string()]
+}
+
+#[test]
+fn test_display_width() {
+    let rows = vec![cells(&["東京", "x"]), cells(&["ab", "y"])];
+    let out = render(&headers(), &rows, &TableStyle::new());
+    assert_eq!(out, "NAME  CITY\n----  ----\n東京  x\nab    y\n");
+    assert_eq!(display_width("東京"), 4);
+    assert_eq!(display_width("café"), 4);
+}
+
+#[test]
+fn test_border_styles() {
+    let rows = vec![cells(&["a|b", "Rome\nParis"])];
+
+    let ascii = render(
+        &headers(),
+        &rows,
+        &TableStyle::new().with_border(BorderStyle::Ascii),
+    );
+    assert_eq!(
+        ascii,
+        "+------+-------+\n\
+         | NAME | CITY  |\n\
+         +------+-------+\n\
+         | a|b  | Rome  |\n\
+         |      | Paris |\n\
+         +------+-------+\n"
+    );
+
+    let boxed = render(
+        &headers(),
+        &rows,
+        &TableStyle::new().with_border(BorderStyle::Box),
+    );
+    assert_eq!(
+        boxed,
+        "┌──────┬───────┐\n\
+         │ NAME │ CITY  │\n\
+         ├──────┼───────┤\n\
+         │ a|b  │ Rome  │\n\
+         │      │ Paris │\n\
+         └──────┴───────┘\n"
+    );
+}
+
+#[test]
+fn test_wrap_and_truncate() {
+    let rows = vec![cells(&["the quick brown fox", "Reykjavik"])];
+
+    let wrapped = render(
+        &headers(),
+        &rows,
+        &TableStyle::new().with_max_column_width(6, Overflow::Wrap),
+    );
+    assert_eq!(
+        wrapped,
+        "NAME   CITY\n\
+         -----  ------\n\
+         the    Reykja\n\
+         quick  vik\n\
+         brown\n\
+         fox\n"
+    );
+
+    let truncated = render(
+        &headers(),
+        &rows,
+        &TableStyle::new().with_max_column_width(6, Overflow::Truncate),
+    );
+    assert_eq!(truncated, "NAME    CITY\n------  ------\nthe q…  Reykj…\n");
+}
+
+#[test]
+fn test_vertical_layout() {
+    let rows = vec![cells(&["Ann", "Oslo"]), cells(&["Bo", ""])];
+    let expected = "-[ RECORD 1 ]\n\
+                    NAME | Ann\n\
+                    CITY | Oslo\n\
+                    -[ RECORD 2 ]\n\
+                    NAME | Bo\n\
+                    CITY |\n";
+    let style = TableStyle::new().with_layout(Layout::Vertical);
+    assert_eq!(render(&headers(), &rows, &style), expected);
+
+    // Auto only switches when the table does not fit
+    let style = TableStyle::new().with_layout(Layout::Auto);
+    assert!(render(&headers(), &rows, &style).starts_with("NAME"));
+    let style = style.with_max_width(8);
+    assert_eq!(render(&headers(), &rows, &style), expected);
+}
+
+#[test]
+fn test_color() {
+    let rows = vec![cells(&["Ann", "Oslo"])];
+    let out = render(&headers(), &rows, &TableStyle::new().with_color(true));
+    assert_eq!(
+        out.lines().next().unwrap(),
+        "\x1b[1;36mNAME\x1b[0m  \x1b[1;36mCITY\x1b[0m"
+    );
+    // Values are not colored
+    assert_eq!(out.lines().nth(2).unwrap(), "Ann   Oslo");
+}
+
+#[test]
+fn test_export_text_with_table_style() {
+    let template = "Value NAME (\\S+)\n\nStart\n  ^${NAME} -> Record\n";
+    let mut fsm = TextFSM::from_string(template).unwrap();
+    let records = fsm.parse_string("Zoë\n", None).unwrap();
+    let options =
+        ExportOptions::new().with_table_style(TableStyle::new().with_border(BorderStyle::Ascii));
+    let out = records.export_with(OutputFormat::Text, &options).unwrap();
+    assert_eq!(out, "+------+\n| NAME |\n+------+\n| Zoë  |\n+------+\n");
+}
+
+#[test]
+fn test_markdown_export_ignores_table_style() {
+    let template = "Value NAME (.+)\n\nStart\n  ^${NAME} -> Record\n";
+    let mut fsm = TextFSM::from_string(template).unwrap();
+    let records = fsm.parse_string("a|b\\c\n", None).unwrap();
+    // Markdown is written by the Markdown export only, never colored
+    let options = ExportOptions::new().with_table_style(TableStyle::new().with_color(true));
+    let out = records
+        .export_with(OutputFormat::Markdown, &options)
+        .unwrap();
+    assert_eq!(out, "| NAME |\n| ---- |\n| a\\|b\\\\c |\n");
+}
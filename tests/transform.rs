@@ -0,0 +1,102 @@
+use std::io::Cursor;
+use textfsm_rs::transform::Pipeline;
+use textfsm_rs::{DataRecord, DataRecordConversion, TextFSM, Value};
+
+const TEMPLATE: &str = r"Value Key INTF (\S+)
+Value DESCRIPTION (.*)
+Value MEMBERS (\S*)
+
+Start
+  ^${INTF}\s+members=${MEMBERS}\s+desc=${DESCRIPTION}$$ -> Record
+";
+
+const INPUT: &str = "Po1 members=Gi0/1,Gi0/2 desc=  uplink  
+Po2 members= desc=
+";
+
+fn single(s: &str) -> Value {
+    Value::Single(s.to_string())
+}
+
+fn pipeline() -> Pipeline {
+    Pipeline::new()
+        .trim()
+        .drop_empty()
+        .rename_common()
+        .split("MEMBERS", ",")
+        .unwrap()
+        .map(|r: &mut DataRecord| {
+            let members = r.get("MEMBERS").cloned();
+            let count = match members {
+                Some(Value::List(l)) => l.len(),
+                _ => 0,
+            };
+            r.insert("MEMBER_COUNT".to_string(), count.to_string());
+        })
+}
+
+#[test]
+fn test_pipeline_parse_string() {
+    let mut fsm = TextFSM::from_string(TEMPLATE)
+        .unwrap()
+        .with_pipeline(pipeline());
+    let records = fsm.parse_string(INPUT, None).unwrap();
+    assert_eq!(records.len(), 2);
+
+    let po1 = &records[0];
+    assert_eq!(po1.get("INTERFACE"), Some(&single("Po1")));
+    assert_eq!(po1.get("INTF"), None);
+    assert_eq!(po1.get("DESCRIPTION"), Some(&single("uplink")));
+    assert_eq!(
+        po1.get("MEMBERS"),
+        Some(&Value::List(vec!["Gi0/1".to_string(), "Gi0/2".to_string()]))
+    );
+    assert_eq!(po1.get("MEMBER_COUNT"), Some(&single("2")));
+    assert_eq!(
+        po1.record_key.as_ref().unwrap().to_string(),
+        "INTERFACE=Po1"
+    );
+
+    let po2 = &records[1];
+    assert_eq!(po2.get("DESCRIPTION"), None);
+    assert_eq!(po2.get("MEMBERS"), None);
+    assert_eq!(po2.get("MEMBER_COUNT"), Some(&single("0")));
+
+    assert_eq!(
+        fsm.header(Some(DataRecordConversion::LowercaseKeys)),
+        vec!["interface", "description", "members"]
+    );
+}
+
+#[test]
+fn test_pipeline_parse_reader() {
+    let mut fsm = TextFSM::from_string(TEMPLATE)
+        .unwrap()
+        .with_pipeline(pipeline().lowercase_keys());
+    let expected = fsm.parse_string(INPUT, None).unwrap();
+
+    let fsm = TextFSM::from_string(TEMPLATE)
+        .unwrap()
+        .with_pipeline(pipeline().lowercase_keys());
+    let streamed: Vec<DataRecord> = fsm
+        .parse_reader(Cursor::new(INPUT))
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(streamed, expected);
+    assert_eq!(
+        streamed[0].record_key.as_ref().unwrap().to_string(),
+        "interface=Po1"
+    );
+}
+
+#[test]
+fn test_conversion_after_pipeline() {
+    let mut fsm = TextFSM::from_string(TEMPLATE)
+        .unwrap()
+        .with_pipeline(Pipeline::new().rename([("INTF", "PORT")]));
+    let records = fsm
+        .parse_string(INPUT, Some(DataRecordConversion::LowercaseKeys))
+        .unwrap();
+    assert_eq!(records[0].get("port"), Some(&single("Po1")));
+    assert!(Pipeline::new().split("X", "(").is_err());
+}
@@ -0,0 +1,114 @@
+use textfsm_rs::export::{RecordMetadata, RecordSink};
+use textfsm_rs::xml::element_name;
+use textfsm_rs::{ExportOptions, LineRange, OutputFormat, Pipeline, TextFSM, TextFsmExport};
+
+const TEMPLATE: &str = r"Value Required CPU (\S+)
+Value INTERFACE (\S+)
+Value List VLANS (\d+)
+
+Start
+  ^cpu ${CPU} if ${INTERFACE}
+  ^  vlan ${VLANS}
+  ^! -> Record
+";
+
+const INPUT: &str = "cpu 3% if Gi1\n  vlan 10\n  vlan 20\n!\n";
+
+fn fsm() -> TextFSM {
+    TextFSM::from_string(TEMPLATE)
+        .unwrap()
+        .with_pipeline(Pipeline::new().rename([("CPU", "5min cpu"), ("INTERFACE", "if:name")]))
+}
+
+#[test]
+fn test_element_name() {
+    assert_eq!(element_name("INTERFACE"), "INTERFACE");
+    assert_eq!(element_name("rx-bytes.total"), "rx-bytes.total");
+    assert_eq!(element_name("5MIN_CPU"), "_5MIN_CPU");
+    assert_eq!(element_name("IN/OUT"), "IN_OUT");
+    assert_eq!(element_name("if:name"), "if_name");
+    assert_eq!(element_name("my field"), "my_field");
+    assert_eq!(element_name("-x"), "_-x");
+    assert_eq!(element_name("XmlData"), "_XmlData");
+    assert_eq!(element_name(""), "_");
+}
+
+#[test]
+fn test_xml_export_well_formed() {
+    let records = fsm().parse_string(INPUT, None).unwrap();
+    let xml = records.export(OutputFormat::Xml).unwrap();
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<results>\n"));
+    assert!(xml.contains("    <_5min_cpu name=\"5min cpu\">3%</_5min_cpu>\n"));
+    assert!(xml.contains("    <if_name name=\"if:name\">Gi1</if_name>\n"));
+    assert!(
+        xml.contains("    <VLANS>\n      <item>10</item>\n      <item>20</item>\n    </VLANS>\n")
+    );
+    assert!(xml.ends_with("</results>"));
+
+    let options = ExportOptions::new().with_xml_tags("cpus", "1cpu");
+    let xml = records.export_with(OutputFormat::Xml, &options).unwrap();
+    assert!(xml.contains("<cpus>\n  <_1cpu>\n"));
+    assert!(xml.ends_with("  </_1cpu>\n</cpus>"));
+}
+
+#[test]
+fn test_xml_metadata_attributes() {
+    let records = fsm().parse_string(INPUT, None).unwrap();
+    let metadata = RecordMetadata {
+        template: Some("cpu.textfsm".into()),
+        command: Some("show <cpu>".into()),
+        ..Default::default()
+    };
+    let mut sink = RecordSink::new(vec![], OutputFormat::Xml).with_metadata(metadata);
+    sink.write_record_at(&records[0], Some(LineRange { start: 1, end: 4 }))
+        .unwrap();
+    let xml = String::from_utf8(sink.finish().unwrap()).unwrap();
+    assert!(xml.contains("<results template=\"cpu.textfsm\" command=\"show &lt;cpu&gt;\">\n"));
+    assert!(xml.contains("  <record start_line=\"1\" end_line=\"4\">\n"));
+}
+
+#[test]
+fn test_xml_schema() {
+    let xsd = fsm().xml_schema(None, &ExportOptions::new());
+    assert!(xsd.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<xs:schema"));
+    assert!(xsd.contains("<xs:element name=\"results\">"));
+    assert!(xsd.contains("<xs:element name=\"record\" minOccurs=\"0\" maxOccurs=\"unbounded\">"));
+    // Required Values must be present
+    assert!(xsd.contains("<xs:element name=\"_5min_cpu\" minOccurs=\"1\">"));
+    assert!(xsd.contains("<xs:attribute name=\"name\" type=\"xs:string\" fixed=\"if:name\"/>"));
+    assert!(xsd.contains("<xs:element name=\"VLANS\" minOccurs=\"0\">"));
+    assert!(xsd.contains("<xs:element name=\"item\" type=\"xs:string\""));
+    assert!(xsd.contains("<xs:attribute name=\"start_line\" type=\"xs:positiveInteger\"/>"));
+}
+
+#[test]
+fn test_xml_invalid_characters() {
+    let mut rec = textfsm_rs::DataRecord::new();
+    rec.insert("NAME".into(), "a\x08b\x00c\td\u{ffff}".into());
+    let xml = vec![rec].export(OutputFormat::Xml).unwrap();
+    // XML 1.0 does not allow these characters even as references
+    assert!(xml.contains("<NAME>a\u{fffd}b\u{fffd}c\td\u{fffd}</NAME>"));
+    assert!(!xml.contains('\x08'));
+}
+
+#[test]
+fn test_xml_colliding_names() {
+    const COLLIDING: &str = r"Value IN_OUT (\S+)
+Value PORTS (\S+)
+
+Start
+  ^${IN_OUT} ${PORTS} -> Record
+";
+    let mut fsm = TextFSM::from_string(COLLIDING)
+        .unwrap()
+        .with_pipeline(Pipeline::new().rename([("PORTS", "IN/OUT")]));
+    let records = fsm.parse_string("1 2\n", None).unwrap();
+    let xml = records.export(OutputFormat::Xml).unwrap();
+    assert!(xml.contains("    <IN_OUT>1</IN_OUT>\n"));
+    assert!(xml.contains("    <IN_OUT_2 name=\"IN/OUT\">2</IN_OUT_2>\n"));
+
+    let xsd = fsm.xml_schema(None, &ExportOptions::new());
+    assert_eq!(xsd.matches("<xs:element name=\"IN_OUT\"").count(), 1);
+    assert!(xsd.contains("<xs:element name=\"IN_OUT_2\" minOccurs=\"0\">"));
+    assert!(xsd.contains("fixed=\"IN/OUT\"/>"));
+}